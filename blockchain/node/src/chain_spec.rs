@@ -1,6 +1,6 @@
 use blockchain_society_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	AccountId, AuraConfig, BalancesConfig, DiscordConfig, GenesisConfig, GrandpaConfig, Signature,
+	SudoConfig, SystemConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -8,6 +8,9 @@ use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{IdentifyAccount, Verify};
 
+/// Guild registered on development chains, with the sudo account as its bot.
+const DEV_GUILD: u64 = 1;
+
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
@@ -147,8 +150,33 @@ fn testnet_genesis(
 		},
 		sudo: SudoConfig {
 			// Assign network admin rights.
-			key: root_key,
+			key: root_key.clone(),
 		},
 		transaction_payment: Default::default(),
+		discord: DiscordConfig {
+			guilds: vec![(DEV_GUILD, b"Development".to_vec())],
+			// A bot with every capability.
+			bots: vec![(
+				DEV_GUILD,
+				root_key.clone(),
+				b"Development bot".to_vec(),
+				DEV_GUILD,
+				u32::MAX,
+			)],
+			roles: vec![
+				// @everyone can view channels, send messages and read their history.
+				(DEV_GUILD, DEV_GUILD, b"@everyone".to_vec(), 0, (1 << 10) | (1 << 11) | (1 << 16)),
+				// Administrator.
+				(DEV_GUILD, 2, b"Admin".to_vec(), 1, 1 << 3),
+			],
+			members: endowed_accounts
+				.iter()
+				.cloned()
+				.map(|k| {
+					let roles = if k == root_key { vec![2] } else { vec![] };
+					(DEV_GUILD, k, roles)
+				})
+				.collect(),
+		},
 	}
 }
@@ -32,7 +32,7 @@ pub use frame_support::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use pallet_balances::Call as BalancesCall;
 /// Import the template pallet.
@@ -96,12 +96,13 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// The version of the runtime specification. A full node will not attempt to use its native
 	//   runtime in substitute for the on-chain Wasm runtime unless all of `spec_name`,
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
-	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
-	//   the compatible custom types.
-	spec_version: 100,
+	// This value started at 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
+	//   the compatible custom types, and is bumped whenever the runtime logic changes.
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	// Bumped whenever the encoding of calls changes, which invalidates signed transactions.
+	transaction_version: 2,
 };
 
 /// This determines the average expected block time that we are targeting.
@@ -274,10 +275,49 @@ impl pallet_sudo::Config for Runtime {
 	type Call = Call;
 }
 
+parameter_types! {
+	pub const MaxScheduledPerBlock: u32 = 50;
+	pub const MaxNameLength: u32 = 100;
+	pub const MaxReasonLength: u32 = 512;
+	pub const MaxRolesPerMember: u32 = 250;
+	pub const MaxOverwritesPerChannel: u32 = 100;
+	pub const MaxBotThreshold: u32 = 16;
+	pub const BotProposalLifetime: BlockNumber = 10 * MINUTES;
+	pub const MaxOpenBotProposals: u32 = 32;
+	pub const MaxTokenGates: u32 = 16;
+	pub const TokenGateCheckPeriod: BlockNumber = HOURS;
+	pub const DiscordPalletId: PalletId = PalletId(*b"py/dscrd");
+	pub const RoleDeposit: Balance = 5_000;
+	pub const MemberDeposit: Balance = 1_000;
+	pub const BanDepositPerByte: Balance = 10;
+	pub const MaxSpendApprovals: u32 = 8;
+	pub const MaxSpendHistory: u32 = 100;
+}
+
 /// Configure the pallet-template in pallets/template.
 impl pallet_discord::Config for Runtime {
 	type Event = Event;
 	type DiscordId = u64;
+	type Signature = Signature;
+	type Signer = <Signature as Verify>::Signer;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type MaxNameLength = MaxNameLength;
+	type MaxReasonLength = MaxReasonLength;
+	type MaxRolesPerMember = MaxRolesPerMember;
+	type MaxOverwritesPerChannel = MaxOverwritesPerChannel;
+	type MaxBotThreshold = MaxBotThreshold;
+	type BotProposalLifetime = BotProposalLifetime;
+	type MaxOpenBotProposals = MaxOpenBotProposals;
+	type Currency = Balances;
+	type RoleDeposit = RoleDeposit;
+	type MemberDeposit = MemberDeposit;
+	type BanDepositPerByte = BanDepositPerByte;
+	type MaxTokenGates = MaxTokenGates;
+	type TokenGateCheckPeriod = TokenGateCheckPeriod;
+	type PalletId = DiscordPalletId;
+	type MaxSpendApprovals = MaxSpendApprovals;
+	type MaxSpendHistory = MaxSpendHistory;
+	type WeightInfo = pallet_discord::weights::SubstrateWeight<Runtime>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -295,7 +335,7 @@ construct_runtime!(
 		Balances: pallet_balances,
 		TransactionPayment: pallet_transaction_payment,
 		Sudo: pallet_sudo,
-		Discord: pallet_discord::{Pallet, Call, Storage, Event<T>},
+		Discord: pallet_discord::{Pallet, Call, Config<T>, Storage, Event<T>},
 	}
 );
 
@@ -317,6 +357,18 @@ pub type SignedExtra = (
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
+parameter_types! {
+	/// Guild the unversioned Discord pallet storage belonged to, moved under it by the v1
+	/// migration. Has to match the guild tracked by the live chain before upgrading it.
+	pub const LegacyDiscordGuild: u64 = 1;
+}
+
+/// Storage migrations run on runtime upgrade, in order.
+pub type Migrations = (
+	pallet_discord::migrations::MigrateToV1<Runtime, LegacyDiscordGuild>,
+	pallet_discord::migrations::MigrateToV2<Runtime>,
+);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -324,6 +376,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 impl_runtime_apis! {
@@ -457,6 +510,22 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_discord_runtime_api::DiscordApi<Block, AccountId, u64> for Runtime {
+		fn effective_permissions(
+			guild: u64,
+			member: AccountId,
+			channel: Option<u64>,
+		) -> pallet_discord::primitives::PermissionSet {
+			Discord::effective_permissions(guild, &member, channel)
+		}
+
+		fn bots(
+			guild: u64,
+		) -> Vec<(AccountId, pallet_discord::primitives::BotInfo<u64, Vec<u8>>)> {
+			Discord::bots(guild)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -474,7 +543,7 @@ impl_runtime_apis! {
 			list_benchmark!(list, extra, frame_system, SystemBench::<Runtime>);
 			list_benchmark!(list, extra, pallet_balances, Balances);
 			list_benchmark!(list, extra, pallet_timestamp, Timestamp);
-			list_benchmark!(list, extra, pallet_template, TemplateModule);
+			list_benchmark!(list, extra, pallet_discord, Discord);
 
 			let storage_info = AllPalletsWithSystem::storage_info();
 
@@ -512,7 +581,7 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
 			add_benchmark!(params, batches, pallet_balances, Balances);
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
-			add_benchmark!(params, batches, pallet_template, TemplateModule);
+			add_benchmark!(params, batches, pallet_discord, Discord);
 
 			Ok(batches)
 		}
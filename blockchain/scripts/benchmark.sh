@@ -0,0 +1,20 @@
+#!/usr/bin/env bash
+# This script is meant to be run on Unix/Linux based systems
+set -e
+
+echo "*** Regenerating pallet-discord weights"
+
+cd $(dirname ${BASH_SOURCE[0]})/../..
+
+cargo build --release --features runtime-benchmarks -p blockchain-society-node
+
+./target/release/blockchain-society-node benchmark \
+    --chain=dev \
+    --execution=wasm \
+    --wasm-execution=compiled \
+    --pallet=pallet_discord \
+    --extrinsic='*' \
+    --steps=50 \
+    --repeat=20 \
+    --template=./blockchain/scripts/frame-weight-template.hbs \
+    --output=./pallet-discord/src/weights.rs
@@ -0,0 +1,25 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+// `decl_runtime_apis!` expands to code that clippy considers needlessly verbose.
+#![allow(clippy::too_many_arguments, clippy::unnecessary_mut_passed)]
+
+use codec::Codec;
+use pallet_discord::primitives::{BotInfo, PermissionSet};
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    pub trait DiscordApi<AccountId, DiscordId>
+    where
+        AccountId: Codec,
+        DiscordId: Codec,
+    {
+        /// Permissions `member` effectively holds in `guild`, optionally inside `channel`.
+        fn effective_permissions(
+            guild: DiscordId,
+            member: AccountId,
+            channel: Option<DiscordId>,
+        ) -> PermissionSet;
+
+        /// The bots of `guild` along with their name, application id and capabilities.
+        fn bots(guild: DiscordId) -> Vec<(AccountId, BotInfo<DiscordId, Vec<u8>>)>;
+    }
+}
@@ -0,0 +1,811 @@
+//! Benchmarking setup for pallet-discord
+
+use super::*;
+
+use crate::primitives::*;
+#[allow(unused)]
+use crate::Pallet as Discord;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::traits::{Currency, Get, Hooks};
+use frame_system::RawOrigin;
+use sp_core::{crypto::KeyTypeId, sr25519};
+use sp_runtime::traits::{Hash, IdentifyAccount, Saturating};
+use sp_std::{vec, vec::Vec};
+
+const SEED: u32 = 0;
+
+/// Key type the link challenge is signed with.
+const KEY_TYPE: KeyTypeId = KeyTypeId(*b"disc");
+
+/// Position of the roles held by moderators, above every role they act on.
+const MODERATOR_POSITION: u64 = 100;
+
+fn id<T: Config>(id: u32) -> T::DiscordId {
+    id.into()
+}
+
+fn max_name<T: Config>() -> Vec<u8> {
+    vec![b'x'; T::MaxNameLength::get() as usize]
+}
+
+fn guild_input<T: Config>(guild: T::DiscordId) -> GuildInputOf<T> {
+    Guild {
+        id: guild,
+        name: max_name::<T>(),
+        icon: max_name::<T>(),
+        owner_id: guild,
+        afk_channel_id: guild,
+        afk_timeout: 300,
+        verification_level: 0,
+        default_message_notifications: 0,
+        explicit_content_filter: 0,
+        rules_channel_id: guild,
+        system_channel_id: guild,
+    }
+}
+
+/// Gives `who` enough to pay the deposits of every item a benchmark writes.
+fn fund<T: Config>(who: &T::AccountId) {
+    T::Currency::make_free_balance_be(
+        who,
+        T::Currency::minimum_balance().saturating_mul(1_000_000u32.into()),
+    );
+}
+
+/// Registers a guild with its @everyone role and a funded bot, returning both. The guild's
+/// account is funded too, as it pays the deposits of moderators.
+fn setup_guild<T: Config>() -> (T::DiscordId, T::AccountId) {
+    let guild = id::<T>(1);
+    let bot: T::AccountId = whitelisted_caller();
+
+    fund::<T>(&bot);
+    fund::<T>(&Discord::<T>::guild_account(guild));
+
+    Discord::<T>::register_guild(RawOrigin::Root.into(), guild_input::<T>(guild))
+        .expect("guild is not registered yet; qed");
+    Discord::<T>::register_bot(
+        RawOrigin::Root.into(),
+        guild,
+        bot.clone(),
+        max_name::<T>(),
+        guild,
+        BotCapabilities::all(),
+    )
+    .expect("guild was just registered; qed");
+    Discord::<T>::create_role(
+        RawOrigin::Signed(bot.clone()).into(),
+        guild,
+        Some(guild),
+        b"@everyone".to_vec(),
+        0,
+        false,
+        0,
+        PermissionSet::empty(),
+        false,
+    )
+    .expect("bots can create roles; qed");
+
+    (guild, bot)
+}
+
+/// Fills the open proposals of `bot` with actions only it submitted, expiring at `expires_at`.
+fn open_bot_proposals<T: Config>(
+    guild: T::DiscordId,
+    bot: &T::AccountId,
+    expires_at: T::BlockNumber,
+) {
+    for i in 0..T::MaxOpenBotProposals::get() {
+        let hash = T::Hashing::hash_of(&(guild, i));
+        let proposal: BotProposalOf<T> = BotProposal {
+            approvals: vec![bot.clone()]
+                .try_into()
+                .expect("MaxBotThreshold is at least one; qed"),
+            expires_at,
+        };
+        BotProposals::<T>::insert(guild, hash, proposal);
+        OpenBotProposals::<T>::try_mutate(guild, bot, |open| open.try_push(hash))
+            .expect("pushes at most MaxOpenBotProposals hashes; qed");
+    }
+}
+
+/// Creates a role through the bot, returning its index.
+fn create_role<T: Config>(
+    guild: T::DiscordId,
+    bot: &T::AccountId,
+    position: u64,
+    permissions: PermissionSet,
+) -> RoleIndex {
+    let index = NextRoleIndex::<T>::get(guild);
+
+    Discord::<T>::create_role(
+        RawOrigin::Signed(bot.clone()).into(),
+        guild,
+        Some(id::<T>(1_000 + index)),
+        max_name::<T>(),
+        0,
+        false,
+        position,
+        permissions,
+        false,
+    )
+    .expect("bots can create roles; qed");
+
+    index
+}
+
+/// Adds `member` to the guild holding `roles` fresh roles at `position`.
+fn add_member_with_roles<T: Config>(
+    guild: T::DiscordId,
+    bot: &T::AccountId,
+    member: &T::AccountId,
+    roles: u32,
+    position: u64,
+    permissions: PermissionSet,
+) {
+    Discord::<T>::add_member(
+        RawOrigin::Signed(bot.clone()).into(),
+        guild,
+        member.clone(),
+        false,
+        false,
+    )
+    .expect("member is not in the guild yet; qed");
+
+    for _ in 0..roles {
+        let role = create_role::<T>(guild, bot, position, permissions);
+
+        Discord::<T>::assign_role(
+            RawOrigin::Signed(bot.clone()).into(),
+            guild,
+            member.clone(),
+            RoleRef::Index(role),
+        )
+        .expect("member holds fewer than MaxRolesPerMember roles; qed");
+    }
+}
+
+/// A member granted `permission` through `MaxRolesPerMember` roles, the worst case for
+/// permission and hierarchy checks.
+fn setup_moderator<T: Config>(
+    guild: T::DiscordId,
+    bot: &T::AccountId,
+    permission: Permissions,
+) -> T::AccountId {
+    let moderator: T::AccountId = account("moderator", 0, SEED);
+
+    add_member_with_roles::<T>(
+        guild,
+        bot,
+        &moderator,
+        T::MaxRolesPerMember::get(),
+        MODERATOR_POSITION,
+        permission.into(),
+    );
+
+    moderator
+}
+
+/// A member holding `roles` roles below the moderator's.
+fn setup_target<T: Config>(guild: T::DiscordId, bot: &T::AccountId, roles: u32) -> T::AccountId {
+    let target: T::AccountId = account("target", 0, SEED);
+
+    add_member_with_roles::<T>(guild, bot, &target, roles, 1, PermissionSet::empty());
+
+    target
+}
+
+/// Gates `gates` fresh roles on the existential deposit, returning the balance qualifying for
+/// them.
+fn setup_token_gates<T: Config>(
+    guild: T::DiscordId,
+    bot: &T::AccountId,
+    gates: u32,
+) -> BalanceOf<T> {
+    let min_balance = T::Currency::minimum_balance();
+
+    for _ in 0..gates {
+        let role = create_role::<T>(guild, bot, 1, PermissionSet::empty());
+
+        Discord::<T>::set_token_gate(
+            RawOrigin::Signed(bot.clone()).into(),
+            guild,
+            RoleRef::Index(role),
+            Some(min_balance),
+        )
+        .expect("bots can gate roles; qed");
+    }
+
+    min_balance
+}
+
+/// A role members can subscribe to for the existential deposit, returning its index and plan.
+fn setup_subscription_plan<T: Config>(
+    guild: T::DiscordId,
+    bot: &T::AccountId,
+) -> (RoleIndex, SubscriptionPlanOf<T>) {
+    let role = create_role::<T>(guild, bot, 1, PermissionSet::empty());
+    let plan = SubscriptionPlan {
+        price: T::Currency::minimum_balance(),
+        period: 10u32.into(),
+        grace_period: 5u32.into(),
+    };
+
+    Discord::<T>::set_subscription_plan(
+        RawOrigin::Signed(bot.clone()).into(),
+        guild,
+        RoleRef::Index(role),
+        Some(plan.clone()),
+    )
+    .expect("bots can set subscription plans; qed");
+
+    (role, plan)
+}
+
+/// A guild member able to pay for a few periods of `plan`.
+fn setup_subscriber<T: Config>(
+    guild: T::DiscordId,
+    bot: &T::AccountId,
+    index: u32,
+    plan: &SubscriptionPlanOf<T>,
+) -> T::AccountId {
+    let member: T::AccountId = account("subscriber", index, SEED);
+
+    add_member_with_roles::<T>(guild, bot, &member, 0, 0, PermissionSet::empty());
+    T::Currency::make_free_balance_be(&member, plan.price.saturating_mul(10u32.into()));
+
+    member
+}
+
+/// A member holding `MANAGE_GUILD` through `MaxRolesPerMember` roles, the worst case for
+/// checking approvers.
+fn setup_approver<T: Config>(guild: T::DiscordId, bot: &T::AccountId, index: u32) -> T::AccountId {
+    let approver: T::AccountId = account("approver", index, SEED);
+
+    add_member_with_roles::<T>(
+        guild,
+        bot,
+        &approver,
+        T::MaxRolesPerMember::get(),
+        MODERATOR_POSITION,
+        Permissions::MANAGE_GUILD.into(),
+    );
+
+    approver
+}
+
+/// A history of `MaxSpendHistory` spends, which the next spend paid shifts.
+fn fill_spend_history<T: Config>(guild: T::DiscordId) {
+    let history: Vec<SpendOf<T>> = (0..T::MaxSpendHistory::get())
+        .map(|index| Spend {
+            index,
+            beneficiary: account("beneficiary", index, SEED),
+            amount: T::Currency::minimum_balance(),
+            paid_at: frame_system::Pallet::<T>::block_number(),
+        })
+        .collect();
+
+    SpendHistory::<T>::mutate(guild, |spends| {
+        *spends = history
+            .try_into()
+            .expect("history holds MaxSpendHistory spends; qed");
+    });
+}
+
+fn overwrites<T: Config>(o: u32) -> PermissionOverwrites<T> {
+    (0..o)
+        .map(|i| PermissionOverwrite {
+            target: OverwriteTarget::Member(account("overwrite", i, SEED)),
+            allow: PermissionSet::all(),
+            deny: PermissionSet::empty(),
+        })
+        .collect()
+}
+
+benchmarks! {
+    where_clause { where
+        T::Signature: From<sr25519::Signature>,
+        T::Signer: From<sr25519::Public>,
+    }
+
+    register_guild {
+        let guild = id::<T>(1);
+    }: _(RawOrigin::Root, guild_input::<T>(guild))
+    verify {
+        assert!(Guilds::<T>::contains_key(guild));
+    }
+
+    update_guild {
+        let (guild, bot) = setup_guild::<T>();
+    }: _(RawOrigin::Signed(bot), guild_input::<T>(guild))
+
+    register_bot {
+        let (guild, _) = setup_guild::<T>();
+        let bot: T::AccountId = account("bot", 0, SEED);
+    }: _(RawOrigin::Root, guild, bot.clone(), max_name::<T>(), guild, BotCapabilities::all())
+    verify {
+        assert!(Bots::<T>::contains_key(guild, bot));
+    }
+
+    // Every proposal the bot opened is dropped along with it.
+    unregister_bot {
+        let (guild, bot) = setup_guild::<T>();
+        open_bot_proposals::<T>(guild, &bot, T::BotProposalLifetime::get());
+    }: _(RawOrigin::Root, guild, bot.clone())
+    verify {
+        assert!(!Bots::<T>::contains_key(guild, &bot));
+        assert!(BotProposals::<T>::iter_prefix(guild).next().is_none());
+    }
+
+    set_bot_threshold {
+        let (guild, _) = setup_guild::<T>();
+        let threshold = T::MaxBotThreshold::get();
+    }: _(RawOrigin::Root, guild, threshold)
+    verify {
+        assert_eq!(BotThreshold::<T>::get(guild), threshold);
+    }
+
+    approve_bot_action {
+        let (guild, bot) = setup_guild::<T>();
+        let threshold = T::MaxBotThreshold::get();
+        Discord::<T>::set_bot_threshold(RawOrigin::Root.into(), guild, threshold)?;
+        let action = Call::<T>::kick_member { guild, member: account("member", 0, SEED) };
+        // All but one of the other bots already submitted the action, so this approval is
+        // recorded rather than run.
+        for i in 1..threshold.saturating_sub(1) {
+            let other: T::AccountId = account("bot", i, SEED);
+            Discord::<T>::register_bot(
+                RawOrigin::Root.into(),
+                guild,
+                other.clone(),
+                max_name::<T>(),
+                guild,
+                BotCapabilities::all(),
+            )?;
+            Discord::<T>::approve_bot_action(
+                guild,
+                &other,
+                BotCapabilities::KICK_MEMBERS,
+                &action,
+            )?;
+        }
+        // Every proposal the bot opened before has expired and is pruned to make room.
+        open_bot_proposals::<T>(guild, &bot, frame_system::Pallet::<T>::block_number());
+    }: {
+        assert!(!Discord::<T>::approve_bot_action(
+            guild,
+            &bot,
+            BotCapabilities::KICK_MEMBERS,
+            &action,
+        )?);
+    }
+    verify {
+        let hash = T::Hashing::hash_of(&action);
+        assert_eq!(OpenBotProposals::<T>::get(guild, &bot).into_inner(), vec![hash]);
+        assert!(BotProposals::<T>::contains_key(guild, hash));
+    }
+
+    // The guild gates the maximum number of roles, all of which the member qualifies for.
+    add_member {
+        let (guild, bot) = setup_guild::<T>();
+        let min_balance = setup_token_gates::<T>(guild, &bot, T::MaxTokenGates::get());
+        let member: T::AccountId = account("member", 0, SEED);
+        T::Currency::make_free_balance_be(&member, min_balance);
+    }: _(RawOrigin::Signed(bot), guild, member.clone(), false, false)
+    verify {
+        assert!(GuildMembers::<T>::contains_key(guild, &member));
+        assert!(TokenGateChecks::<T>::contains_key(guild, &member));
+    }
+
+    ban_member {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::BAN_MEMBERS);
+        let target = setup_target::<T>(guild, &bot, T::MaxRolesPerMember::get());
+        let reason = vec![b'x'; T::MaxReasonLength::get() as usize];
+        let expires_at = frame_system::Pallet::<T>::block_number() + 10u32.into();
+    }: _(RawOrigin::Signed(moderator), guild, target.clone(), reason, Some(expires_at))
+    verify {
+        assert!(BannedMembers::<T>::contains_key(guild, target));
+    }
+
+    unban_member {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::BAN_MEMBERS);
+        let target = setup_target::<T>(guild, &bot, T::MaxRolesPerMember::get());
+        Discord::<T>::ban_member(
+            RawOrigin::Signed(bot).into(),
+            guild,
+            target.clone(),
+            vec![b'x'; T::MaxReasonLength::get() as usize],
+            None,
+        )?;
+    }: _(RawOrigin::Signed(moderator), guild, target.clone(), true)
+    verify {
+        assert!(GuildMembers::<T>::contains_key(guild, target));
+    }
+
+    kick_member {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::KICK_MEMBERS);
+        let target = setup_target::<T>(guild, &bot, T::MaxRolesPerMember::get());
+    }: _(RawOrigin::Signed(moderator), guild, target.clone())
+    verify {
+        assert!(!GuildMembers::<T>::contains_key(guild, target));
+    }
+
+    set_mute {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::MUTE_MEMBERS);
+        let target = setup_target::<T>(guild, &bot, T::MaxRolesPerMember::get());
+    }: _(RawOrigin::Signed(moderator), guild, target.clone(), true)
+    verify {
+        assert!(GuildMembers::<T>::get(guild, target).map_or(false, |member| member.mute));
+    }
+
+    set_deaf {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::DEAFEN_MEMBERS);
+        let target = setup_target::<T>(guild, &bot, T::MaxRolesPerMember::get());
+    }: _(RawOrigin::Signed(moderator), guild, target.clone(), true)
+    verify {
+        assert!(GuildMembers::<T>::get(guild, target).map_or(false, |member| member.deaf));
+    }
+
+    timeout_member {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::MODERATE_MEMBERS);
+        let target = setup_target::<T>(guild, &bot, T::MaxRolesPerMember::get());
+        let until = frame_system::Pallet::<T>::block_number() + 10u32.into();
+    }: _(RawOrigin::Signed(moderator), guild, target.clone(), Some(until))
+    verify {
+        assert_eq!(
+            GuildMembers::<T>::get(guild, target)
+                .and_then(|member| member.communication_disabled_until),
+            Some(until)
+        );
+    }
+
+    remove_member {
+        let (guild, bot) = setup_guild::<T>();
+        let target = setup_target::<T>(guild, &bot, T::MaxRolesPerMember::get());
+    }: _(RawOrigin::Signed(bot), guild, target.clone())
+    verify {
+        assert!(!GuildMembers::<T>::contains_key(guild, target));
+    }
+
+    create_role {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::MANAGE_ROLES);
+        let index = NextRoleIndex::<T>::get(guild);
+    }: _(
+        RawOrigin::Signed(moderator),
+        guild,
+        Some(id::<T>(2)),
+        max_name::<T>(),
+        0,
+        false,
+        1,
+        PermissionSet::empty(),
+        false
+    )
+    verify {
+        assert!(Roles::<T>::contains_key(guild, index));
+    }
+
+    confirm_role_id {
+        let (guild, bot) = setup_guild::<T>();
+        let index = NextRoleIndex::<T>::get(guild);
+        Discord::<T>::create_role(
+            RawOrigin::Signed(bot.clone()).into(),
+            guild,
+            None,
+            max_name::<T>(),
+            0,
+            false,
+            1,
+            PermissionSet::empty(),
+            false,
+        )?;
+    }: _(RawOrigin::Signed(bot), guild, index, id::<T>(2))
+    verify {
+        assert_eq!(RoleIdToIndex::<T>::get(guild, id::<T>(2)), Some(index));
+    }
+
+    assign_role {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::MANAGE_ROLES);
+        let target = setup_target::<T>(guild, &bot, T::MaxRolesPerMember::get() - 1);
+        let role = create_role::<T>(guild, &bot, 1, PermissionSet::empty());
+    }: _(RawOrigin::Signed(moderator), guild, target.clone(), RoleRef::Id(id::<T>(1_000 + role)))
+    verify {
+        assert!(GuildMembers::<T>::get(guild, target)
+            .map_or(false, |member| member.roles.contains(&role)));
+    }
+
+    update_role {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::MANAGE_ROLES);
+        let role = create_role::<T>(guild, &bot, 1, PermissionSet::empty());
+    }: _(
+        RawOrigin::Signed(moderator),
+        guild,
+        RoleRef::Id(id::<T>(1_000 + role)),
+        max_name::<T>(),
+        1,
+        true,
+        2,
+        Permissions::MANAGE_ROLES.into(),
+        true
+    )
+    verify {
+        assert_eq!(Roles::<T>::get(guild, role).map(|role| role.position), Some(2));
+    }
+
+    // Every member holds the role, the worst case for cleaning up references to it.
+    delete_role {
+        let w in 1 .. 1_000;
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::MANAGE_ROLES);
+        let role = create_role::<T>(guild, &bot, 1, PermissionSet::empty());
+        for i in 1..w {
+            let member: T::AccountId = account("member", i, SEED);
+            Discord::<T>::add_member(
+                RawOrigin::Signed(bot.clone()).into(),
+                guild,
+                member.clone(),
+                false,
+                false,
+            )?;
+            Discord::<T>::assign_role(
+                RawOrigin::Signed(bot.clone()).into(),
+                guild,
+                member,
+                RoleRef::Index(role),
+            )?;
+        }
+    }: _(RawOrigin::Signed(moderator), guild, RoleRef::Id(id::<T>(1_000 + role)), w)
+    verify {
+        assert!(!Roles::<T>::contains_key(guild, role));
+    }
+
+    revoke_role {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::MANAGE_ROLES);
+        let target = setup_target::<T>(guild, &bot, T::MaxRolesPerMember::get());
+        let role = NextRoleIndex::<T>::get(guild) - 1;
+    }: _(RawOrigin::Signed(moderator), guild, target.clone(), RoleRef::Id(id::<T>(1_000 + role)))
+    verify {
+        assert!(GuildMembers::<T>::get(guild, target)
+            .map_or(false, |member| !member.roles.contains(&role)));
+    }
+
+    create_channel {
+        let o in 0 .. T::MaxOverwritesPerChannel::get();
+        let (guild, bot) = setup_guild::<T>();
+        let channel = id::<T>(2);
+    }: _(RawOrigin::Signed(bot), guild, channel, ChannelType::GUILD_TEXT, 1, overwrites::<T>(o))
+    verify {
+        assert!(Channels::<T>::contains_key(guild, channel));
+    }
+
+    update_channel {
+        let o in 0 .. T::MaxOverwritesPerChannel::get();
+        let (guild, bot) = setup_guild::<T>();
+        let channel = id::<T>(2);
+        Discord::<T>::create_channel(
+            RawOrigin::Signed(bot.clone()).into(),
+            guild,
+            channel,
+            ChannelType::GUILD_TEXT,
+            1,
+            overwrites::<T>(o),
+        )?;
+    }: _(RawOrigin::Signed(bot), guild, channel, ChannelType::GUILD_VOICE, 2, overwrites::<T>(o))
+    verify {
+        assert_eq!(Channels::<T>::get(guild, channel).map(|channel| channel.position), Some(2));
+    }
+
+    delete_channel {
+        let (guild, bot) = setup_guild::<T>();
+        let channel = id::<T>(2);
+        Discord::<T>::create_channel(
+            RawOrigin::Signed(bot.clone()).into(),
+            guild,
+            channel,
+            ChannelType::GUILD_TEXT,
+            1,
+            overwrites::<T>(T::MaxOverwritesPerChannel::get()),
+        )?;
+    }: _(RawOrigin::Signed(bot), guild, channel)
+    verify {
+        assert!(!Channels::<T>::contains_key(guild, channel));
+    }
+
+    // Relinking an account to a Discord user linked elsewhere, the worst case.
+    link_account {
+        let (guild, bot) = setup_guild::<T>();
+        let public = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+        let linked = T::Signer::from(public).into_account();
+        let discord_user = id::<T>(2);
+        DiscordUserOf::<T>::insert(&linked, id::<T>(3));
+        AccountOfDiscordUser::<T>::insert(id::<T>(3), &linked);
+        let challenge = Discord::<T>::link_challenge(&linked, discord_user);
+        let signature = sp_io::crypto::sr25519_sign(KEY_TYPE, &public, &challenge)
+            .expect("key was just generated; qed");
+    }: _(RawOrigin::Signed(bot), guild, linked.clone(), discord_user, signature.into())
+    verify {
+        assert_eq!(DiscordUserOf::<T>::get(&linked), Some(discord_user));
+    }
+
+    unlink_account {
+        let member: T::AccountId = account("member", 0, SEED);
+        DiscordUserOf::<T>::insert(&member, id::<T>(2));
+        AccountOfDiscordUser::<T>::insert(id::<T>(2), &member);
+    }: _(RawOrigin::Signed(member.clone()))
+    verify {
+        assert!(!DiscordUserOf::<T>::contains_key(&member));
+    }
+
+    set_token_gate {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::MANAGE_ROLES);
+        let min_balance = setup_token_gates::<T>(guild, &bot, T::MaxTokenGates::get() - 1);
+        let role = create_role::<T>(guild, &bot, 1, PermissionSet::empty());
+    }: _(RawOrigin::Signed(moderator), guild, RoleRef::Index(role), Some(min_balance))
+    verify {
+        assert!(TokenGates::<T>::get(guild).contains(&(role, min_balance)));
+    }
+
+    refresh_token_gates {
+        let (guild, bot) = setup_guild::<T>();
+        let min_balance = setup_token_gates::<T>(guild, &bot, T::MaxTokenGates::get());
+        let member: T::AccountId = account("member", 0, SEED);
+        add_member_with_roles::<T>(guild, &bot, &member, 0, 0, PermissionSet::empty());
+        T::Currency::make_free_balance_be(&member, min_balance);
+    }: _(RawOrigin::Signed(bot), guild, member.clone())
+    verify {
+        assert!(TokenGateChecks::<T>::contains_key(guild, &member));
+        assert_eq!(
+            GuildMembers::<T>::get(guild, &member).unwrap().roles.len() as u32,
+            T::MaxTokenGates::get()
+        );
+    }
+
+    set_subscription_plan {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::MANAGE_ROLES);
+        let role = create_role::<T>(guild, &bot, 1, PermissionSet::empty());
+        let plan = SubscriptionPlan {
+            price: T::Currency::minimum_balance(),
+            period: 10u32.into(),
+            grace_period: 5u32.into(),
+        };
+    }: _(RawOrigin::Signed(moderator), guild, RoleRef::Index(role), Some(plan.clone()))
+    verify {
+        assert_eq!(SubscriptionPlans::<T>::get(guild, role), Some(plan));
+    }
+
+    subscribe {
+        let (guild, bot) = setup_guild::<T>();
+        let (role, plan) = setup_subscription_plan::<T>(guild, &bot);
+        let member = setup_subscriber::<T>(guild, &bot, 0, &plan);
+    }: _(RawOrigin::Signed(member.clone()), guild, RoleRef::Index(role))
+    verify {
+        assert!(Subscriptions::<T>::contains_key(guild, (&member, role)));
+        assert!(GuildMembers::<T>::get(guild, &member).unwrap().roles.contains(&role));
+    }
+
+    cancel_subscription {
+        let (guild, bot) = setup_guild::<T>();
+        let (role, plan) = setup_subscription_plan::<T>(guild, &bot);
+        let member = setup_subscriber::<T>(guild, &bot, 0, &plan);
+        Discord::<T>::subscribe(
+            RawOrigin::Signed(member.clone()).into(),
+            guild,
+            RoleRef::Index(role),
+        )?;
+    }: _(RawOrigin::Signed(member.clone()), guild, RoleRef::Index(role))
+    verify {
+        assert!(!Subscriptions::<T>::get(guild, (&member, role)).unwrap().auto_renew);
+    }
+
+    set_treasury_approvers {
+        let (guild, bot) = setup_guild::<T>();
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::MANAGE_GUILD);
+        let role = create_role::<T>(guild, &bot, 1, PermissionSet::empty());
+        let threshold = T::MaxSpendApprovals::get();
+    }: _(RawOrigin::Signed(moderator), guild, Some(RoleRef::Index(role)), threshold)
+    verify {
+        assert_eq!(TreasuryRole::<T>::get(guild), Some(role));
+        assert_eq!(SpendThreshold::<T>::get(guild), threshold);
+    }
+
+    propose_spend {
+        let (guild, bot) = setup_guild::<T>();
+        let approver = setup_approver::<T>(guild, &bot, 0);
+        let beneficiary: T::AccountId = account("beneficiary", 0, SEED);
+        let amount = T::Currency::minimum_balance();
+        let description = vec![b'x'; T::MaxReasonLength::get() as usize];
+    }: _(RawOrigin::Signed(approver), guild, beneficiary.clone(), amount, description)
+    verify {
+        assert!(SpendProposals::<T>::contains_key(guild, 0));
+    }
+
+    approve_spend {
+        let (guild, bot) = setup_guild::<T>();
+        let threshold = T::MaxSpendApprovals::get();
+        SpendThreshold::<T>::insert(guild, threshold);
+        fill_spend_history::<T>(guild);
+        let beneficiary: T::AccountId = account("beneficiary", 0, SEED);
+        let amount = T::Currency::minimum_balance();
+        let proposer = setup_approver::<T>(guild, &bot, 0);
+        Discord::<T>::propose_spend(
+            RawOrigin::Signed(proposer).into(),
+            guild,
+            beneficiary.clone(),
+            amount,
+            Vec::new(),
+        )?;
+        for i in 1..threshold - 1 {
+            let approver = setup_approver::<T>(guild, &bot, i);
+            Discord::<T>::approve_spend(RawOrigin::Signed(approver).into(), guild, 0)?;
+        }
+        let approver = setup_approver::<T>(guild, &bot, threshold);
+    }: _(RawOrigin::Signed(approver), guild, 0)
+    verify {
+        assert!(!SpendProposals::<T>::contains_key(guild, 0));
+        assert_eq!(T::Currency::free_balance(&beneficiary), amount);
+    }
+
+    cancel_spend {
+        let (guild, bot) = setup_guild::<T>();
+        SpendThreshold::<T>::insert(guild, 2);
+        let proposer = setup_approver::<T>(guild, &bot, 0);
+        Discord::<T>::propose_spend(
+            RawOrigin::Signed(proposer).into(),
+            guild,
+            account("beneficiary", 0, SEED),
+            T::Currency::minimum_balance(),
+            Vec::new(),
+        )?;
+        let moderator = setup_moderator::<T>(guild, &bot, Permissions::MANAGE_GUILD);
+    }: _(RawOrigin::Signed(moderator), guild, 0)
+    verify {
+        assert!(!SpendProposals::<T>::contains_key(guild, 0));
+    }
+
+    // Subscription renewals, the heaviest scheduled task, pushed back as far as they can be.
+    on_initialize {
+        let t in 0 .. T::MaxScheduledPerBlock::get();
+        let (guild, bot) = setup_guild::<T>();
+        let (role, plan) = setup_subscription_plan::<T>(guild, &bot);
+        let mut members = Vec::new();
+        for i in 0..t {
+            let member = setup_subscriber::<T>(guild, &bot, i, &plan);
+            Discord::<T>::subscribe(
+                RawOrigin::Signed(member.clone()).into(),
+                guild,
+                RoleRef::Index(role),
+            )?;
+            members.push(member);
+        }
+        let now = frame_system::Pallet::<T>::block_number() + plan.period;
+        let filler = ScheduledTask::BanExpiry(guild, bot);
+        for i in 0..RENEWAL_LOOKAHEAD - 1 {
+            Scheduled::<T>::mutate(now + plan.period + i.into(), |tasks| {
+                while tasks.try_push(filler.clone()).is_ok() {}
+            });
+        }
+    }: {
+        Discord::<T>::on_initialize(now);
+    }
+    verify {
+        for member in members {
+            assert_eq!(
+                Subscriptions::<T>::get(guild, (&member, role)).unwrap().paid_until,
+                now + plan.period
+            );
+        }
+    }
+
+    impl_benchmark_test_suite!(Discord, crate::mock::new_test_ext(), crate::mock::Test);
+}
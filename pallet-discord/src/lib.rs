@@ -2,16 +2,40 @@
 
 pub use pallet::*;
 
-mod primitives;
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod migrations;
+pub mod origins;
+pub mod primitives;
+pub mod weights;
+
+pub use origins::{EnsurePermission, EnsureRole};
+pub use weights::WeightInfo;
 
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
     use crate::primitives::*;
-    use frame_support::pallet_prelude::*;
+    use frame_support::{
+        pallet_prelude::*,
+        storage,
+        traits::{Currency, ExistenceRequirement, ReservableCurrency},
+        transactional, PalletId,
+    };
     use frame_system::pallet_prelude::*;
-    use sp_runtime::traits::AtLeast32BitUnsigned;
-    use sp_std::vec;
+    use sp_runtime::{
+        traits::{
+            AccountIdConversion, AtLeast32BitUnsigned, Hash, IdentifyAccount, One, Saturating,
+            Verify, Zero,
+        },
+        TransactionOutcome,
+    };
     use sp_std::vec::Vec;
 
     /// Configure the pallet by specifying the parameters and types on which it depends.
@@ -20,46 +44,668 @@ pub mod pallet {
         /// Because this pallet emits events, it depends on the runtime's definition of an event.
         type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 
-        type DiscordId: Parameter + Member + AtLeast32BitUnsigned + Default + Copy;
+        type DiscordId: Parameter
+            + Member
+            + AtLeast32BitUnsigned
+            + Default
+            + Copy
+            + MaxEncodedLen
+            + MaybeSerializeDeserialize;
+
+        /// Signature members produce over a link challenge to prove they own their account.
+        type Signature: Verify<Signer = Self::Signer> + Parameter;
+
+        /// Public key type `Signature` is verified against.
+        type Signer: IdentifyAccount<AccountId = Self::AccountId>;
+
+        /// Maximum number of scheduled tasks, such as ban expiries, run in a single block.
+        #[pallet::constant]
+        type MaxScheduledPerBlock: Get<u32>;
+
+        /// Maximum length of guild and role names, and of guild icon hashes.
+        #[pallet::constant]
+        type MaxNameLength: Get<u32>;
+
+        /// Maximum length of a ban reason.
+        #[pallet::constant]
+        type MaxReasonLength: Get<u32>;
+
+        /// Maximum number of roles a single member can hold.
+        #[pallet::constant]
+        type MaxRolesPerMember: Get<u32>;
+
+        /// Maximum number of permission overwrites on a single channel.
+        #[pallet::constant]
+        type MaxOverwritesPerChannel: Get<u32>;
+
+        /// Maximum number of bots a guild can require to submit the same action before it runs.
+        #[pallet::constant]
+        type MaxBotThreshold: Get<u32>;
+
+        /// Number of blocks bots have to reach the threshold on an action before the approvals
+        /// submitted so far are dropped.
+        #[pallet::constant]
+        type BotProposalLifetime: Get<Self::BlockNumber>;
+
+        /// Maximum number of actions a single bot can have waiting for the threshold at once.
+        #[pallet::constant]
+        type MaxOpenBotProposals: Get<u32>;
+
+        /// Currency token-gated roles and subscriptions are paid in, and deposits are reserved
+        /// in.
+        type Currency: ReservableCurrency<Self::AccountId>;
+
+        /// Deposit reserved for every role created.
+        #[pallet::constant]
+        type RoleDeposit: Get<BalanceOf<Self>>;
+
+        /// Deposit reserved for every member record added.
+        #[pallet::constant]
+        type MemberDeposit: Get<BalanceOf<Self>>;
+
+        /// Deposit reserved per byte of a ban's reason.
+        #[pallet::constant]
+        type BanDepositPerByte: Get<BalanceOf<Self>>;
+
+        /// Maximum number of token-gated roles in a single guild.
+        #[pallet::constant]
+        type MaxTokenGates: Get<u32>;
+
+        /// Number of blocks between two re-evaluations of a member's token-gated roles.
+        #[pallet::constant]
+        type TokenGateCheckPeriod: Get<Self::BlockNumber>;
+
+        /// Id the treasury accounts of guilds are derived from.
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+
+        /// Maximum number of approvals a guild can require for spends from its treasury.
+        #[pallet::constant]
+        type MaxSpendApprovals: Get<u32>;
+
+        /// Number of paid spends kept in the history of each guild.
+        #[pallet::constant]
+        type MaxSpendHistory: Get<u32>;
+
+        /// Weight information for extrinsics in this pallet.
+        type WeightInfo: WeightInfo;
     }
 
-    pub type GuildMemberOf<T> = GuildMember<<T as frame_system::Config>::AccountId>;
+    /// How many blocks past the requested one a task may be pushed back when blocks are full.
+    const SCHEDULE_LOOKAHEAD: u32 = 10;
+
+    /// How many blocks past the end of the paid period a renewal may be pushed back, wider than
+    /// other tasks so a busy scheduler isn't taken for a member failing to pay.
+    pub(crate) const RENEWAL_LOOKAHEAD: u32 = 10 * SCHEDULE_LOOKAHEAD;
+
+    /// Prefix of the challenge members sign to link their account to a Discord user.
+    pub const LINK_CHALLENGE_PREFIX: &[u8] = b"blockchain-society:link-discord-user";
+
+    pub type NameOf<T> = BoundedVec<u8, <T as pallet::Config>::MaxNameLength>;
+
+    pub type ReasonOf<T> = BoundedVec<u8, <T as pallet::Config>::MaxReasonLength>;
+
+    pub type GuildMemberOf<T> = GuildMember<
+        <T as frame_system::Config>::AccountId,
+        <T as frame_system::Config>::BlockNumber,
+        BoundedVec<RoleIndex, <T as pallet::Config>::MaxRolesPerMember>,
+    >;
+
+    /// A ban from the discord server represented as (member, ban reason, expiry block).
+    pub type Ban<T> = (
+        GuildMemberOf<T>,
+        ReasonOf<T>,
+        Option<<T as frame_system::Config>::BlockNumber>,
+    );
+
+    pub type ScheduledTaskOf<T> =
+        ScheduledTask<<T as frame_system::Config>::AccountId, <T as pallet::Config>::DiscordId>;
+
+    pub type BotProposalOf<T> = BotProposal<
+        <T as frame_system::Config>::BlockNumber,
+        BoundedVec<<T as frame_system::Config>::AccountId, <T as pallet::Config>::MaxBotThreshold>,
+    >;
+
+    pub type RoleOf<T> = Role<<T as pallet::Config>::DiscordId, NameOf<T>>;
 
-    /// A ban from the discord server represented as (member, ban reason).
-    pub type Ban<T> = (GuildMemberOf<T>, Vec<u8>);
+    pub type BalanceOf<T> = <<T as pallet::Config>::Currency as Currency<
+        <T as frame_system::Config>::AccountId,
+    >>::Balance;
 
-    pub type RoleOf<T> = Role<<T as pallet::Config>::DiscordId>;
+    pub type SubscriptionPlanOf<T> =
+        SubscriptionPlan<BalanceOf<T>, <T as frame_system::Config>::BlockNumber>;
+
+    pub type SpendProposalOf<T> = SpendProposal<
+        <T as frame_system::Config>::AccountId,
+        BalanceOf<T>,
+        ReasonOf<T>,
+        BoundedVec<
+            <T as frame_system::Config>::AccountId,
+            <T as pallet::Config>::MaxSpendApprovals,
+        >,
+    >;
+
+    pub type SpendOf<T> = Spend<
+        <T as frame_system::Config>::AccountId,
+        BalanceOf<T>,
+        <T as frame_system::Config>::BlockNumber,
+    >;
+
+    pub type GuildOf<T> = Guild<<T as pallet::Config>::DiscordId, NameOf<T>>;
+
+    pub type BotInfoOf<T> = BotInfo<<T as pallet::Config>::DiscordId, NameOf<T>>;
+
+    /// A guild as submitted to `register_guild` and `update_guild`, before its names are bounded.
+    pub type GuildInputOf<T> = Guild<<T as pallet::Config>::DiscordId, Vec<u8>>;
+
+    pub type ChannelOf<T> = Channel<
+        <T as pallet::Config>::DiscordId,
+        BoundedVec<
+            PermissionOverwrite<<T as frame_system::Config>::AccountId>,
+            <T as pallet::Config>::MaxOverwritesPerChannel,
+        >,
+    >;
+
+    pub type PermissionOverwrites<T> =
+        Vec<PermissionOverwrite<<T as frame_system::Config>::AccountId>>;
+
+    /// Who is running an administrative function.
+    pub enum Actor<AccountId> {
+        /// One of the guild's bots, mirroring an action already validated by Discord.
+        Bot(AccountId),
+        /// A guild member acting through the permissions granted by their roles.
+        Member(AccountId),
+    }
+
+    /// The current storage version.
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
 
     #[pallet::pallet]
     #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::storage_version(STORAGE_VERSION)]
+    #[pallet::generate_storage_info]
     pub struct Pallet<T>(_);
 
+    #[pallet::storage]
+    #[pallet::getter(fn get_guild)]
+    pub type Guilds<T: Config> = StorageMap<_, Blake2_128Concat, T::DiscordId, GuildOf<T>>;
+
     #[pallet::storage]
     #[pallet::getter(fn get_member)]
-    pub type GuildMembers<T: Config> =
-        StorageMap<_, Blake2_128Concat, T::AccountId, GuildMemberOf<T>>;
+    pub type GuildMembers<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DiscordId,
+        Blake2_128Concat,
+        T::AccountId,
+        GuildMemberOf<T>,
+    >;
 
     #[pallet::storage]
     #[pallet::getter(fn get_banned_member)]
-    pub type BannedMembers<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, Ban<T>>;
+    pub type BannedMembers<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, T::DiscordId, Blake2_128Concat, T::AccountId, Ban<T>>;
 
     #[pallet::storage]
     #[pallet::getter(fn get_role)]
-    pub type Roles<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, RoleOf<T>>;
+    pub type Roles<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, T::DiscordId, Twox64Concat, RoleIndex, RoleOf<T>>;
+
+    /// Index the next role created in a guild will be stored under.
+    #[pallet::storage]
+    #[pallet::getter(fn next_role_index)]
+    pub type NextRoleIndex<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DiscordId, RoleIndex, ValueQuery>;
+
+    /// Reverse index from a role's Discord snowflake to its on-chain index. Replaces a
+    /// `RoleIdToName` index: names aren't unique, so the snowflake resolves to the index the role
+    /// is stored under instead.
+    #[pallet::storage]
+    #[pallet::getter(fn get_role_index)]
+    pub type RoleIdToIndex<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DiscordId,
+        Blake2_128Concat,
+        T::DiscordId,
+        RoleIndex,
+    >;
 
     #[pallet::storage]
     #[pallet::getter(fn get_bot)]
-    pub type Bots<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
+    pub type Bots<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DiscordId,
+        Blake2_128Concat,
+        T::AccountId,
+        BotInfoOf<T>,
+    >;
+
+    /// Number of bots that have to submit the same action before it runs. Up to one, every bot
+    /// acts on its own.
+    #[pallet::storage]
+    #[pallet::getter(fn bot_threshold)]
+    pub type BotThreshold<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DiscordId, u32, ValueQuery>;
+
+    /// Bots that submitted an action, by the hash of the call, while it waits for the threshold.
+    #[pallet::storage]
+    #[pallet::getter(fn bot_approvals)]
+    pub type BotProposals<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, T::DiscordId, Identity, T::Hash, BotProposalOf<T>>;
+
+    /// Call hashes of the actions each bot submitted that may still be waiting for the
+    /// threshold, bounding how many proposals a single bot can keep in storage.
+    #[pallet::storage]
+    #[pallet::getter(fn open_bot_proposals)]
+    pub type OpenBotProposals<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DiscordId,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<T::Hash, T::MaxOpenBotProposals>,
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn get_channel)]
+    pub type Channels<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DiscordId,
+        Blake2_128Concat,
+        T::DiscordId,
+        ChannelOf<T>,
+    >;
+
+    /// The Discord user each account is linked to.
+    #[pallet::storage]
+    #[pallet::getter(fn discord_user_of)]
+    pub type DiscordUserOf<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::DiscordId>;
+
+    /// The account each Discord user is linked to.
+    #[pallet::storage]
+    #[pallet::getter(fn account_of_discord_user)]
+    pub type AccountOfDiscordUser<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DiscordId, T::AccountId>;
+
+    /// Bumped on every link so a signed challenge can't be replayed.
+    #[pallet::storage]
+    #[pallet::getter(fn link_nonce)]
+    pub type LinkNonce<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// Roles of each guild granted while a member's free balance is at least the given amount.
+    #[pallet::storage]
+    #[pallet::getter(fn token_gates)]
+    pub type TokenGates<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::DiscordId,
+        BoundedVec<(RoleIndex, BalanceOf<T>), T::MaxTokenGates>,
+        ValueQuery,
+    >;
+
+    /// Members whose token-gated roles are re-evaluated every `TokenGateCheckPeriod` blocks.
+    #[pallet::storage]
+    pub type TokenGateChecks<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, T::DiscordId, Blake2_128Concat, T::AccountId, ()>;
+
+    /// Depositor and amount of the deposits held for the items of each guild. Items written at
+    /// genesis or by migrations hold none.
+    #[pallet::storage]
+    #[pallet::getter(fn get_deposit)]
+    pub type Deposits<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DiscordId,
+        Blake2_128Concat,
+        DepositKey<T::AccountId>,
+        (T::AccountId, BalanceOf<T>),
+    >;
+
+    /// Price and billing period of the roles of each guild members can subscribe to.
+    #[pallet::storage]
+    #[pallet::getter(fn subscription_plan)]
+    pub type SubscriptionPlans<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DiscordId,
+        Twox64Concat,
+        RoleIndex,
+        SubscriptionPlanOf<T>,
+    >;
+
+    /// Subscriptions of each guild, by (member, role).
+    #[pallet::storage]
+    #[pallet::getter(fn subscription)]
+    pub type Subscriptions<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DiscordId,
+        Blake2_128Concat,
+        (T::AccountId, RoleIndex),
+        Subscription<T::BlockNumber>,
+    >;
+
+    /// Role whose holders can propose and approve spends from each guild's treasury, besides
+    /// members holding `MANAGE_GUILD`.
+    #[pallet::storage]
+    #[pallet::getter(fn treasury_role)]
+    pub type TreasuryRole<T: Config> = StorageMap<_, Blake2_128Concat, T::DiscordId, RoleIndex>;
+
+    #[pallet::type_value]
+    pub fn DefaultSpendThreshold() -> u32 {
+        2
+    }
+
+    /// Number of approvals spends from each guild's treasury need, the proposer's included. It's
+    /// never below 2, so no member can pay a spend out on their own.
+    #[pallet::storage]
+    #[pallet::getter(fn spend_threshold)]
+    pub type SpendThreshold<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DiscordId, u32, ValueQuery, DefaultSpendThreshold>;
+
+    /// Index the next spend proposed in a guild will be stored under.
+    #[pallet::storage]
+    #[pallet::getter(fn next_spend_index)]
+    pub type NextSpendIndex<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DiscordId, SpendIndex, ValueQuery>;
+
+    /// Spends of each guild waiting for approvals.
+    #[pallet::storage]
+    #[pallet::getter(fn spend_proposal)]
+    pub type SpendProposals<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DiscordId,
+        Twox64Concat,
+        SpendIndex,
+        SpendProposalOf<T>,
+    >;
+
+    /// The last `MaxSpendHistory` spends paid out of each guild's treasury, oldest first.
+    #[pallet::storage]
+    #[pallet::getter(fn spend_history)]
+    pub type SpendHistory<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::DiscordId,
+        BoundedVec<SpendOf<T>, T::MaxSpendHistory>,
+        ValueQuery,
+    >;
+
+    /// Tasks to run at the start of each block.
+    #[pallet::storage]
+    #[pallet::getter(fn scheduled)]
+    pub type Scheduled<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::BlockNumber,
+        BoundedVec<ScheduledTaskOf<T>, T::MaxScheduledPerBlock>,
+        ValueQuery,
+    >;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        /// Guilds registered at genesis, as (guild, name).
+        pub guilds: Vec<(T::DiscordId, Vec<u8>)>,
+        /// Bots authorized at genesis, as (guild, bot, name, application id, capability bits).
+        pub bots: Vec<(T::DiscordId, T::AccountId, Vec<u8>, T::DiscordId, u32)>,
+        /// Roles created at genesis, as (guild, Discord id, name, position, permission bits).
+        /// A role sharing the guild's id is its @everyone role.
+        pub roles: Vec<(T::DiscordId, T::DiscordId, Vec<u8>, u64, u64)>,
+        /// Members added at genesis, as (guild, member, Discord ids of their roles).
+        pub members: Vec<(T::DiscordId, T::AccountId, Vec<T::DiscordId>)>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            Self {
+                guilds: Default::default(),
+                bots: Default::default(),
+                roles: Default::default(),
+                members: Default::default(),
+            }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+            for (guild, name) in &self.guilds {
+                assert!(
+                    !Guilds::<T>::contains_key(guild),
+                    "guild registered twice in genesis"
+                );
+
+                Guilds::<T>::insert(
+                    guild,
+                    Guild {
+                        id: *guild,
+                        name: name
+                            .clone()
+                            .try_into()
+                            .expect("guild name longer than MaxNameLength"),
+                        icon: Default::default(),
+                        owner_id: Default::default(),
+                        afk_channel_id: Default::default(),
+                        afk_timeout: Default::default(),
+                        verification_level: Default::default(),
+                        default_message_notifications: Default::default(),
+                        explicit_content_filter: Default::default(),
+                        rules_channel_id: Default::default(),
+                        system_channel_id: Default::default(),
+                    },
+                );
+            }
+
+            for (guild, bot, name, application_id, capabilities) in &self.bots {
+                assert!(
+                    Guilds::<T>::contains_key(guild),
+                    "bot of a guild missing from genesis"
+                );
+                assert!(
+                    !Bots::<T>::contains_key(guild, bot),
+                    "bot registered twice in genesis"
+                );
+
+                Bots::<T>::insert(
+                    guild,
+                    bot,
+                    BotInfo {
+                        name: name
+                            .clone()
+                            .try_into()
+                            .expect("bot name longer than MaxNameLength"),
+                        application_id: *application_id,
+                        capabilities: BotCapabilities::from_bits_truncate(*capabilities),
+                    },
+                );
+            }
+
+            for (guild, id, name, position, permissions) in &self.roles {
+                assert!(
+                    Guilds::<T>::contains_key(guild),
+                    "role of a guild missing from genesis"
+                );
+                assert!(
+                    !RoleIdToIndex::<T>::contains_key(guild, id),
+                    "role id used twice in genesis"
+                );
+
+                let name: NameOf<T> = name
+                    .clone()
+                    .try_into()
+                    .expect("role name longer than MaxNameLength");
+
+                let index = NextRoleIndex::<T>::get(guild);
+                NextRoleIndex::<T>::insert(guild, index + 1);
+
+                Roles::<T>::insert(
+                    guild,
+                    index,
+                    Role {
+                        id: Some(*id),
+                        name,
+                        color: 0,
+                        hoist: false,
+                        position: *position,
+                        permissions: PermissionSet::from(*permissions),
+                        managed: false,
+                        mentionable: false,
+                    },
+                );
+                RoleIdToIndex::<T>::insert(guild, id, index);
+            }
+
+            for (guild, member, roles) in &self.members {
+                assert!(
+                    Guilds::<T>::contains_key(guild),
+                    "member of a guild missing from genesis"
+                );
+
+                let roles: Vec<RoleIndex> = roles
+                    .iter()
+                    .map(|id| {
+                        RoleIdToIndex::<T>::get(guild, id)
+                            .expect("member role missing from genesis")
+                    })
+                    .collect();
+
+                GuildMembers::<T>::insert(
+                    guild,
+                    member,
+                    GuildMember {
+                        account: member.clone(),
+                        roles: roles
+                            .try_into()
+                            .expect("member holds more than MaxRolesPerMember roles"),
+                        deaf: false,
+                        mute: false,
+                        communication_disabled_until: None,
+                    },
+                );
+            }
+        }
+    }
 
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
-        BotAdded(T::AccountId),
-        MemberAdded(T::AccountId),
-        MemberBanned(T::AccountId, Vec<u8>),
-        /// Name, color, hoist, position, permissions, mentionable
-        RoleCreated(Vec<u8>, u64, bool, u64, Vec<Permissions>, bool),
-        RoleAssigned(T::AccountId, Vec<u8>),
+        /// Guild, bot, application id, capabilities
+        BotAdded(T::DiscordId, T::AccountId, T::DiscordId, BotCapabilities),
+        /// Guild, bot
+        BotRemoved(T::DiscordId, T::AccountId),
+        /// Guild, threshold
+        BotThresholdSet(T::DiscordId, u32),
+        /// Guild, bot, call hash, approvals so far
+        BotActionApproved(T::DiscordId, T::AccountId, T::Hash, u32),
+        /// Guild, call hash
+        BotProposalExpired(T::DiscordId, T::Hash),
+        GuildRegistered(T::DiscordId),
+        GuildUpdated(T::DiscordId),
+        /// Guild, member
+        MemberAdded(T::DiscordId, T::AccountId),
+        /// Guild, member, reason, expiry block
+        MemberBanned(T::DiscordId, T::AccountId, Vec<u8>, Option<T::BlockNumber>),
+        /// Guild, member
+        BanExpired(T::DiscordId, T::AccountId),
+        /// Guild, member, restored
+        MemberUnbanned(T::DiscordId, T::AccountId, bool),
+        /// Guild, member
+        MemberKicked(T::DiscordId, T::AccountId),
+        /// Guild, member
+        MemberRemoved(T::DiscordId, T::AccountId),
+        /// Guild, member, muted
+        MemberMuted(T::DiscordId, T::AccountId, bool),
+        /// Guild, member, deafened
+        MemberDeafened(T::DiscordId, T::AccountId, bool),
+        /// Guild, member, timed out until
+        MemberTimedOut(T::DiscordId, T::AccountId, Option<T::BlockNumber>),
+        /// Guild, member
+        TimeoutExpired(T::DiscordId, T::AccountId),
+        /// Guild, role, name, color, hoist, position, permissions, mentionable
+        RoleCreated(
+            T::DiscordId,
+            RoleIndex,
+            Vec<u8>,
+            u64,
+            bool,
+            u64,
+            PermissionSet,
+            bool,
+        ),
+        /// Guild, role, Discord id
+        RoleIdConfirmed(T::DiscordId, RoleIndex, T::DiscordId),
+        /// Guild, member, role
+        RoleAssigned(T::DiscordId, T::AccountId, RoleIndex),
+        /// Guild, role, name, color, hoist, position, permissions, mentionable
+        RoleUpdated(
+            T::DiscordId,
+            RoleIndex,
+            Vec<u8>,
+            u64,
+            bool,
+            u64,
+            PermissionSet,
+            bool,
+        ),
+        /// Guild, role
+        RoleDeleted(T::DiscordId, RoleIndex),
+        /// Guild, member, role
+        RoleRevoked(T::DiscordId, T::AccountId, RoleIndex),
+        /// Guild, channel, channel type, position
+        ChannelCreated(T::DiscordId, T::DiscordId, ChannelType, u64),
+        /// Guild, channel, channel type, position
+        ChannelUpdated(T::DiscordId, T::DiscordId, ChannelType, u64),
+        /// Guild, channel
+        ChannelDeleted(T::DiscordId, T::DiscordId),
+        /// Account, Discord user
+        AccountLinked(T::AccountId, T::DiscordId),
+        /// Account, previous Discord user, new Discord user
+        AccountRelinked(T::AccountId, T::DiscordId, T::DiscordId),
+        /// Account, Discord user
+        AccountUnlinked(T::AccountId, T::DiscordId),
+        /// Guild, role, minimum free balance, or `None` if the gate was removed
+        TokenGateSet(T::DiscordId, RoleIndex, Option<BalanceOf<T>>),
+        /// Guild, member, role
+        TokenGatedRoleGranted(T::DiscordId, T::AccountId, RoleIndex),
+        /// Guild, member, role
+        TokenGatedRoleRevoked(T::DiscordId, T::AccountId, RoleIndex),
+        /// Guild, member whose next token gate check couldn't be scheduled, until
+        /// `refresh_token_gates` enrolls them again
+        TokenGateCheckNotScheduled(T::DiscordId, T::AccountId),
+        /// Guild, role, plan, or `None` if the plan was removed
+        SubscriptionPlanSet(T::DiscordId, RoleIndex, Option<SubscriptionPlanOf<T>>),
+        /// Guild, member, role, paid until
+        Subscribed(T::DiscordId, T::AccountId, RoleIndex, T::BlockNumber),
+        /// Guild, member, role, paid until
+        SubscriptionRenewed(T::DiscordId, T::AccountId, RoleIndex, T::BlockNumber),
+        /// Guild, member, role, end of the grace period
+        SubscriptionPaymentFailed(T::DiscordId, T::AccountId, RoleIndex, T::BlockNumber),
+        /// Guild, member, role
+        SubscriptionCancelled(T::DiscordId, T::AccountId, RoleIndex),
+        /// Guild, member, role
+        SubscriptionExpired(T::DiscordId, T::AccountId, RoleIndex),
+        /// Guild, treasury role, number of approvals spends need
+        TreasuryApproversSet(T::DiscordId, Option<RoleIndex>, u32),
+        /// Guild, spend, proposer, beneficiary, amount
+        SpendProposed(
+            T::DiscordId,
+            SpendIndex,
+            T::AccountId,
+            T::AccountId,
+            BalanceOf<T>,
+        ),
+        /// Guild, spend, approver, number of approvals
+        SpendApproved(T::DiscordId, SpendIndex, T::AccountId, u32),
+        /// Guild, spend, beneficiary, amount
+        SpendPaid(T::DiscordId, SpendIndex, T::AccountId, BalanceOf<T>),
+        /// Guild, spend
+        SpendCancelled(T::DiscordId, SpendIndex),
     }
 
     #[pallet::error]
@@ -68,141 +714,2441 @@ pub mod pallet {
         NoneValue,
         /// Errors should have helpful documentation associated with them.
         StorageOverflow,
-        /// Only the bot account has permission to run administrative functions.
+        /// Only the guild's bot accounts with the required capability, or members holding the
+        /// required permission, can run administrative functions.
         NoPermission,
+        /// The account is already a bot of the guild.
+        BotAlreadyRegistered,
+        /// The account isn't a bot of the guild.
+        NotABot,
+        /// The bot already submitted this action, or the member already approved this spend.
+        AlreadyApproved,
+        /// The bot already has `MaxOpenBotProposals` actions waiting for the threshold.
+        TooManyBotProposals,
+        /// The bot threshold is higher than `MaxBotThreshold`, or the spend threshold higher
+        /// than `MaxSpendApprovals`.
+        ThresholdTooHigh,
+        /// The spend threshold doesn't require an approval besides the proposer's.
+        ThresholdTooLow,
 
         NotAMemberOfTheGuild,
+        /// Banned accounts have to be unbanned before they can be added back.
+        MemberIsBanned,
+        /// The account is already a member of the guild.
+        AlreadyMember,
+        /// The account isn't banned from the guild.
+        NotBanned,
+        /// Members can only moderate members whose highest role is below their own.
+        MemberAboveActor,
 
         RoleDoesntExist,
+        /// The member doesn't hold the role.
+        RoleNotAssigned,
+        /// The member already holds the role.
+        RoleAlreadyAssigned,
+        /// Members can only manage roles positioned below their own highest role.
+        RoleAboveActor,
+        /// Members can only grant permissions they hold themselves.
+        PermissionsAboveActor,
+        /// The role already has a Discord id.
+        RoleIdAlreadyConfirmed,
+        /// Another role of the guild already uses this Discord id.
+        RoleIdAlreadyInUse,
+        /// A guild with this id is already registered.
+        GuildAlreadyRegistered,
+        /// The guild hasn't been registered yet.
+        GuildNotRegistered,
+        /// A channel with this id already exists in the guild.
+        ChannelAlreadyExists,
+
+        ChannelDoesntExist,
+        /// The link signature doesn't match the account's current challenge.
+        InvalidLinkSignature,
+        /// The account is already linked to this Discord user.
+        AlreadyLinked,
+        /// The Discord user is linked to another account, which has to unlink it first.
+        DiscordUserAlreadyLinked,
+        /// The account isn't linked to any Discord user.
+        AccountNotLinked,
+        /// Expiry blocks have to be in the future.
+        ExpiryInThePast,
+        /// Every block around the requested one already has the maximum of scheduled tasks.
+        SchedulerFull,
+        /// The name is longer than `MaxNameLength`.
+        NameTooLong,
+        /// The ban reason is longer than `MaxReasonLength`.
+        ReasonTooLong,
+        /// The member already holds `MaxRolesPerMember` roles.
+        TooManyRoles,
+        /// The channel has more than `MaxOverwritesPerChannel` permission overwrites.
+        TooManyOverwrites,
+        /// The witness is lower than the number of members, banned members and channels of the
+        /// guild.
+        InvalidWitness,
+        /// The guild already has `MaxTokenGates` token-gated roles.
+        TooManyTokenGates,
+        /// Subscription periods can't be empty, and grace periods have to be shorter.
+        InvalidSubscriptionPlan,
+        /// Members can't subscribe to the role.
+        NoSubscriptionPlan,
+        /// The member is already subscribed to the role.
+        AlreadySubscribed,
+        /// The member isn't subscribed to the role.
+        NotSubscribed,
+        /// No spend proposal with this index.
+        UnknownSpend,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let tasks = Scheduled::<T>::take(now).into_inner();
+            let weight = T::WeightInfo::on_initialize(tasks.len() as u32);
+
+            for task in tasks {
+                Self::run_task(now, task);
+            }
+
+            weight
+        }
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        #[pallet::weight(1000)]
-        pub fn register_bot(origin: OriginFor<T>, account: T::AccountId) -> DispatchResult {
+        #[pallet::weight(T::WeightInfo::register_guild())]
+        pub fn register_guild(origin: OriginFor<T>, guild: GuildInputOf<T>) -> DispatchResult {
+            ensure_root(origin)?;
+
+            ensure!(
+                !Guilds::<T>::contains_key(guild.id),
+                Error::<T>::GuildAlreadyRegistered
+            );
+
+            let guild_id = guild.id;
+            Guilds::<T>::insert(guild_id, Self::bound_guild(guild)?);
+
+            Self::deposit_event(Event::GuildRegistered(guild_id));
+
+            Ok(())
+        }
+
+        #[pallet::weight(
+            T::WeightInfo::update_guild()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn update_guild(origin: OriginFor<T>, guild: GuildInputOf<T>) -> DispatchResult {
+            let action = Call::<T>::update_guild {
+                guild: guild.clone(),
+            };
+
+            Guilds::<T>::try_mutate(guild.id, |old_guild| -> DispatchResult {
+                if Self::ensure_bot(origin, guild.id, BotCapabilities::MANAGE_GUILD, &action)?
+                    .is_none()
+                {
+                    return Ok(());
+                }
+
+                ensure!(old_guild.is_some(), Error::<T>::GuildNotRegistered);
+
+                let guild_id = guild.id;
+                *old_guild = Some(Self::bound_guild(guild)?);
+
+                Self::deposit_event(Event::GuildUpdated(guild_id));
+
+                Ok(())
+            })
+        }
+
+        /// Authorizes `account` as a bot of `guild`, limited to `capabilities`. To change a
+        /// bot's metadata it has to be unregistered and registered again.
+        #[pallet::weight(T::WeightInfo::register_bot())]
+        pub fn register_bot(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            account: T::AccountId,
+            name: Vec<u8>,
+            application_id: T::DiscordId,
+            capabilities: BotCapabilities,
+        ) -> DispatchResult {
             ensure_root(origin)?;
-            Bots::<T>::insert(account.clone(), ());
 
-            Self::deposit_event(Event::BotAdded(account));
+            ensure!(
+                Guilds::<T>::contains_key(guild),
+                Error::<T>::GuildNotRegistered
+            );
+            ensure!(
+                !Bots::<T>::contains_key(guild, &account),
+                Error::<T>::BotAlreadyRegistered
+            );
+
+            Bots::<T>::insert(
+                guild,
+                account.clone(),
+                BotInfo {
+                    name: name.try_into().map_err(|_| Error::<T>::NameTooLong)?,
+                    application_id,
+                    capabilities,
+                },
+            );
+
+            Self::deposit_event(Event::BotAdded(
+                guild,
+                account,
+                application_id,
+                capabilities,
+            ));
 
             Ok(())
         }
 
-        #[pallet::weight(1000)]
-        pub fn add_member(origin: OriginFor<T>, member: T::AccountId) -> DispatchResult {
+        /// Revokes the access of one of the guild's bots, e.g. after its key leaked.
+        #[pallet::weight(T::WeightInfo::unregister_bot())]
+        pub fn unregister_bot(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            account: T::AccountId,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+
             ensure!(
-                Bots::<T>::get(ensure_signed(origin)?).is_some(),
-                Error::<T>::NoPermission
+                Bots::<T>::take(guild, &account).is_some(),
+                Error::<T>::NotABot
+            );
+
+            // The bot's approvals no longer count, and proposals only it submitted are dropped.
+            for hash in OpenBotProposals::<T>::take(guild, &account) {
+                BotProposals::<T>::mutate_exists(guild, hash, |proposal| {
+                    let abandoned = proposal.as_mut().map_or(false, |proposal| {
+                        proposal.approvals.retain(|approval| *approval != account);
+                        proposal.approvals.is_empty()
+                    });
+
+                    if abandoned {
+                        *proposal = None;
+                    }
+                });
+            }
+
+            Self::deposit_event(Event::BotRemoved(guild, account));
+
+            Ok(())
+        }
+
+        /// Requires `threshold` bots of the guild to submit the same action before it runs.
+        /// Approvals are dropped if the threshold isn't reached within `BotProposalLifetime`
+        /// blocks of the first one, and each bot can keep at most `MaxOpenBotProposals` actions
+        /// waiting.
+        #[pallet::weight(T::WeightInfo::set_bot_threshold())]
+        pub fn set_bot_threshold(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            threshold: u32,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+
+            ensure!(
+                Guilds::<T>::contains_key(guild),
+                Error::<T>::GuildNotRegistered
+            );
+            ensure!(
+                threshold <= T::MaxBotThreshold::get(),
+                Error::<T>::ThresholdTooHigh
+            );
+
+            BotThreshold::<T>::insert(guild, threshold);
+
+            Self::deposit_event(Event::BotThresholdSet(guild, threshold));
+
+            Ok(())
+        }
+
+        /// Adds `member` to the guild. If the guild gates roles on balances, the member's gated
+        /// roles are applied and they are enrolled in the periodic token gate checks right away,
+        /// unless the scheduler is full.
+        #[pallet::weight(
+            T::WeightInfo::add_member()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn add_member(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            member: T::AccountId,
+            deaf: bool,
+            mute: bool,
+        ) -> DispatchResult {
+            let action = Call::<T>::add_member {
+                guild,
+                member: member.clone(),
+                deaf,
+                mute,
+            };
+
+            let bot =
+                match Self::ensure_bot(origin, guild, BotCapabilities::MANAGE_MEMBERS, &action)? {
+                    Some(bot) => bot,
+                    None => return Ok(()),
+                };
+
+            ensure!(
+                !BannedMembers::<T>::contains_key(guild, &member),
+                Error::<T>::MemberIsBanned
+            );
+            ensure!(
+                !GuildMembers::<T>::contains_key(guild, &member),
+                Error::<T>::AlreadyMember
             );
 
+            Self::reserve_deposit(
+                guild,
+                DepositKey::Member(member.clone()),
+                bot,
+                T::MemberDeposit::get(),
+            )?;
+
             GuildMembers::<T>::insert(
+                guild,
                 member.clone(),
                 GuildMember {
                     account: member.clone(),
-                    roles: vec![],
-                    deaf: false,
-                    mute: false,
+                    roles: BoundedVec::default(),
+                    deaf,
+                    mute,
+                    communication_disabled_until: None,
                 },
             );
 
-            Self::deposit_event(Event::MemberAdded(member));
+            Self::deposit_event(Event::MemberAdded(guild, member.clone()));
+
+            if !TokenGates::<T>::get(guild).is_empty() {
+                // A full scheduler doesn't keep the member out of the guild.
+                if Self::enroll_in_token_gate_checks(guild, &member).is_err() {
+                    Self::deposit_event(Event::TokenGateCheckNotScheduled(guild, member.clone()));
+                }
+                Self::apply_token_gates(guild, &member);
+            }
 
             Ok(())
         }
 
-        #[pallet::weight(1000)]
+        #[pallet::weight(
+            T::WeightInfo::ban_member()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
         pub fn ban_member(
             origin: OriginFor<T>,
+            guild: T::DiscordId,
             member: T::AccountId,
             reason: Vec<u8>,
+            expires_at: Option<T::BlockNumber>,
         ) -> DispatchResult {
-            GuildMembers::<T>::try_mutate(member.clone(), |guild_member| -> DispatchResult {
-                ensure!(
-                    Bots::<T>::get(ensure_signed(origin)?).is_some(),
-                    Error::<T>::NoPermission
-                );
+            let action = Call::<T>::ban_member {
+                guild,
+                member: member.clone(),
+                reason: reason.clone(),
+                expires_at,
+            };
 
-                let guild_member = guild_member
+            GuildMembers::<T>::try_mutate(guild, member.clone(), |guild_member| -> DispatchResult {
+                let actor = match Self::ensure_bot_or_permission(
+                    origin,
+                    guild,
+                    BotCapabilities::BAN_MEMBERS,
+                    Permissions::BAN_MEMBERS,
+                    &action,
+                )? {
+                    Some(actor) => actor,
+                    None => return Ok(()),
+                };
+
+                Self::ensure_above_member(guild, &actor, &member)?;
+
+                let bounded_reason: ReasonOf<T> = reason
+                    .clone()
+                    .try_into()
+                    .map_err(|_| Error::<T>::ReasonTooLong)?;
+
+                let mut guild_member = guild_member
                     .take()
                     .ok_or(Error::<T>::NotAMemberOfTheGuild)?;
 
-                BannedMembers::<T>::insert(member.clone(), (guild_member, reason.clone()));
+                // Subscribed roles aren't kept for a restoring unban, as they're no longer paid.
+                guild_member
+                    .roles
+                    .retain(|role| !Self::end_subscription(guild, &member, *role));
+
+                if let Some(expires_at) = expires_at {
+                    ensure!(
+                        expires_at > frame_system::Pallet::<T>::block_number(),
+                        Error::<T>::ExpiryInThePast
+                    );
+
+                    Self::schedule(expires_at, ScheduledTask::BanExpiry(guild, member.clone()))?;
+                }
 
-                Self::deposit_event(Event::MemberBanned(member, reason));
+                // The member's record keeps its deposit while banned, the reason is paid for apart.
+                Self::reserve_deposit(
+                    guild,
+                    DepositKey::Ban(member.clone()),
+                    Self::depositor(guild, &actor),
+                    T::BanDepositPerByte::get().saturating_mul((reason.len() as u32).into()),
+                )?;
+
+                BannedMembers::<T>::insert(
+                    guild,
+                    member.clone(),
+                    (guild_member, bounded_reason, expires_at),
+                );
+
+                Self::deposit_event(Event::MemberBanned(guild, member, reason, expires_at));
 
                 Ok(())
             })
         }
 
-        #[pallet::weight(1000)]
-        pub fn create_role(
+        /// Lifts a ban. With `restore` the member is put back in the guild with the roles they
+        /// held when banned, otherwise their record is dropped and they have to be added again.
+        #[pallet::weight(
+            T::WeightInfo::unban_member()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn unban_member(
             origin: OriginFor<T>,
-            name: Vec<u8>,
-            color: u64,
-            hoist: bool,
-            position: u64,
-            permissions: Vec<Permissions>,
-            mentionable: bool,
+            guild: T::DiscordId,
+            member: T::AccountId,
+            restore: bool,
         ) -> DispatchResult {
-            ensure!(
-                Bots::<T>::get(ensure_signed(origin)?).is_some(),
-                Error::<T>::NoPermission
-            );
+            let action = Call::<T>::unban_member {
+                guild,
+                member: member.clone(),
+                restore,
+            };
 
-            Roles::<T>::insert(
-                name.clone(),
-                Role {
-                    id: None,
-                    name: name.clone(),
-                    color,
-                    hoist,
-                    position,
-                    permissions: permissions.clone(),
-                    managed: false,
-                    mentionable,
-                },
-            );
+            if Self::ensure_bot_or_permission(
+                origin,
+                guild,
+                BotCapabilities::BAN_MEMBERS,
+                Permissions::BAN_MEMBERS,
+                &action,
+            )?
+            .is_none()
+            {
+                return Ok(());
+            }
 
-            Self::deposit_event(Event::RoleCreated(
-                name,
-                color,
-                hoist,
-                position,
-                permissions,
-                mentionable,
-            ));
+            let (mut guild_member, _, _) =
+                BannedMembers::<T>::take(guild, &member).ok_or(Error::<T>::NotBanned)?;
+
+            Self::release_deposit(guild, DepositKey::Ban(member.clone()));
+
+            if restore {
+                guild_member.communication_disabled_until = None;
+                GuildMembers::<T>::insert(guild, &member, guild_member);
+            } else {
+                Self::release_deposit(guild, DepositKey::Member(member.clone()));
+            }
+
+            Self::deposit_event(Event::MemberUnbanned(guild, member, restore));
 
             Ok(())
         }
 
-        #[pallet::weight(1000)]
-        pub fn assign_role(
+        #[pallet::weight(
+            T::WeightInfo::kick_member()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn kick_member(
             origin: OriginFor<T>,
-            target: T::AccountId,
-            role_name: Vec<u8>,
+            guild: T::DiscordId,
+            member: T::AccountId,
         ) -> DispatchResult {
-            GuildMembers::<T>::try_mutate(target.clone(), |guild_member| -> DispatchResult {
-                ensure!(
-                    Bots::<T>::get(ensure_signed(origin)?).is_some(),
-                    Error::<T>::NoPermission
-                );
+            let action = Call::<T>::kick_member {
+                guild,
+                member: member.clone(),
+            };
 
-                ensure!(
-                    Roles::<T>::get(role_name.clone()).is_some(),
-                    Error::<T>::RoleDoesntExist
-                );
+            let actor = match Self::ensure_bot_or_permission(
+                origin,
+                guild,
+                BotCapabilities::KICK_MEMBERS,
+                Permissions::KICK_MEMBERS,
+                &action,
+            )? {
+                Some(actor) => actor,
+                None => return Ok(()),
+            };
 
-                let mut old_member = guild_member
-                    .take()
-                    .ok_or(Error::<T>::NotAMemberOfTheGuild)?;
-                old_member.roles.push(role_name.clone());
+            Self::ensure_above_member(guild, &actor, &member)?;
 
-                *guild_member = Some(old_member);
+            let guild_member =
+                GuildMembers::<T>::take(guild, &member).ok_or(Error::<T>::NotAMemberOfTheGuild)?;
 
-                Self::deposit_event(Event::RoleAssigned(target, role_name));
+            for role in guild_member.roles {
+                Self::end_subscription(guild, &member, role);
+            }
+
+            Self::release_deposit(guild, DepositKey::Member(member.clone()));
+
+            Self::deposit_event(Event::MemberKicked(guild, member));
+
+            Ok(())
+        }
+
+        #[pallet::weight(
+            T::WeightInfo::set_mute()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn set_mute(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            member: T::AccountId,
+            mute: bool,
+        ) -> DispatchResult {
+            let action = Call::<T>::set_mute {
+                guild,
+                member: member.clone(),
+                mute,
+            };
+
+            GuildMembers::<T>::try_mutate(guild, member.clone(), |guild_member| -> DispatchResult {
+                let actor = match Self::ensure_bot_or_permission(
+                    origin,
+                    guild,
+                    BotCapabilities::MODERATE_MEMBERS,
+                    Permissions::MUTE_MEMBERS,
+                    &action,
+                )? {
+                    Some(actor) => actor,
+                    None => return Ok(()),
+                };
+
+                Self::ensure_above_member(guild, &actor, &member)?;
+
+                let guild_member = guild_member
+                    .as_mut()
+                    .ok_or(Error::<T>::NotAMemberOfTheGuild)?;
+                guild_member.mute = mute;
+
+                Self::deposit_event(Event::MemberMuted(guild, member, mute));
 
                 Ok(())
             })
         }
+
+        #[pallet::weight(
+            T::WeightInfo::set_deaf()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn set_deaf(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            member: T::AccountId,
+            deaf: bool,
+        ) -> DispatchResult {
+            let action = Call::<T>::set_deaf {
+                guild,
+                member: member.clone(),
+                deaf,
+            };
+
+            GuildMembers::<T>::try_mutate(guild, member.clone(), |guild_member| -> DispatchResult {
+                let actor = match Self::ensure_bot_or_permission(
+                    origin,
+                    guild,
+                    BotCapabilities::MODERATE_MEMBERS,
+                    Permissions::DEAFEN_MEMBERS,
+                    &action,
+                )? {
+                    Some(actor) => actor,
+                    None => return Ok(()),
+                };
+
+                Self::ensure_above_member(guild, &actor, &member)?;
+
+                let guild_member = guild_member
+                    .as_mut()
+                    .ok_or(Error::<T>::NotAMemberOfTheGuild)?;
+                guild_member.deaf = deaf;
+
+                Self::deposit_event(Event::MemberDeafened(guild, member, deaf));
+
+                Ok(())
+            })
+        }
+
+        /// Times a member out until block `until`, or lifts their timeout if `until` is `None`.
+        #[pallet::weight(
+            T::WeightInfo::timeout_member()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn timeout_member(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            member: T::AccountId,
+            until: Option<T::BlockNumber>,
+        ) -> DispatchResult {
+            let action = Call::<T>::timeout_member {
+                guild,
+                member: member.clone(),
+                until,
+            };
+
+            GuildMembers::<T>::try_mutate(guild, member.clone(), |guild_member| -> DispatchResult {
+                let actor = match Self::ensure_bot_or_permission(
+                    origin,
+                    guild,
+                    BotCapabilities::MODERATE_MEMBERS,
+                    Permissions::MODERATE_MEMBERS,
+                    &action,
+                )? {
+                    Some(actor) => actor,
+                    None => return Ok(()),
+                };
+
+                Self::ensure_above_member(guild, &actor, &member)?;
+
+                let guild_member = guild_member
+                    .as_mut()
+                    .ok_or(Error::<T>::NotAMemberOfTheGuild)?;
+
+                if let Some(until) = until {
+                    ensure!(
+                        until > frame_system::Pallet::<T>::block_number(),
+                        Error::<T>::ExpiryInThePast
+                    );
+
+                    Self::schedule(until, ScheduledTask::TimeoutExpiry(guild, member.clone()))?;
+                }
+
+                guild_member.communication_disabled_until = until;
+
+                Self::deposit_event(Event::MemberTimedOut(guild, member, until));
+
+                Ok(())
+            })
+        }
+
+        /// Mirrors a member leaving the guild on their own.
+        #[pallet::weight(
+            T::WeightInfo::remove_member()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn remove_member(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            member: T::AccountId,
+        ) -> DispatchResult {
+            let action = Call::<T>::remove_member {
+                guild,
+                member: member.clone(),
+            };
+
+            if Self::ensure_bot(origin, guild, BotCapabilities::MANAGE_MEMBERS, &action)?.is_none()
+            {
+                return Ok(());
+            }
+
+            let guild_member =
+                GuildMembers::<T>::take(guild, &member).ok_or(Error::<T>::NotAMemberOfTheGuild)?;
+
+            for role in guild_member.roles {
+                Self::end_subscription(guild, &member, role);
+            }
+
+            Self::release_deposit(guild, DepositKey::Member(member.clone()));
+
+            Self::deposit_event(Event::MemberRemoved(guild, member));
+
+            Ok(())
+        }
+
+        /// Creates a role in `guild`. A role mirrored from Discord carries its `id`, so retrying
+        /// the call fails with `RoleIdAlreadyInUse`. A role created on-chain first has no id until
+        /// `confirm_role_id`, and nothing to tell a retry from a second role: retrying creates a
+        /// duplicate, which the bot removes with `delete_role`.
+        #[allow(clippy::too_many_arguments)]
+        #[pallet::weight(
+            T::WeightInfo::create_role()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn create_role(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            id: Option<T::DiscordId>,
+            name: Vec<u8>,
+            color: u64,
+            hoist: bool,
+            position: u64,
+            permissions: PermissionSet,
+            mentionable: bool,
+        ) -> DispatchResult {
+            let action = Call::<T>::create_role {
+                guild,
+                id,
+                name: name.clone(),
+                color,
+                hoist,
+                position,
+                permissions,
+                mentionable,
+            };
+
+            let actor = match Self::ensure_bot_or_permission(
+                origin,
+                guild,
+                BotCapabilities::MANAGE_ROLES,
+                Permissions::MANAGE_ROLES,
+                &action,
+            )? {
+                Some(actor) => actor,
+                None => return Ok(()),
+            };
+
+            Self::ensure_above_role(guild, &actor, position)?;
+            Self::ensure_holds_permissions(guild, &actor, permissions)?;
+
+            let bounded_name: NameOf<T> = name
+                .clone()
+                .try_into()
+                .map_err(|_| Error::<T>::NameTooLong)?;
+
+            if let Some(id) = id {
+                ensure!(
+                    !RoleIdToIndex::<T>::contains_key(guild, id),
+                    Error::<T>::RoleIdAlreadyInUse
+                );
+            }
+
+            let index = NextRoleIndex::<T>::get(guild);
+            NextRoleIndex::<T>::insert(
+                guild,
+                index.checked_add(1).ok_or(Error::<T>::StorageOverflow)?,
+            );
+
+            Self::reserve_deposit(
+                guild,
+                DepositKey::Role(index),
+                Self::depositor(guild, &actor),
+                T::RoleDeposit::get(),
+            )?;
+
+            Roles::<T>::insert(
+                guild,
+                index,
+                Role {
+                    id,
+                    name: bounded_name,
+                    color,
+                    hoist,
+                    position,
+                    permissions,
+                    managed: false,
+                    mentionable,
+                },
+            );
+
+            if let Some(id) = id {
+                RoleIdToIndex::<T>::insert(guild, id, index);
+            }
+
+            Self::deposit_event(Event::RoleCreated(
+                guild,
+                index,
+                name,
+                color,
+                hoist,
+                position,
+                permissions,
+                mentionable,
+            ));
+
+            Ok(())
+        }
+
+        #[pallet::weight(
+            T::WeightInfo::confirm_role_id()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn confirm_role_id(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            role: RoleIndex,
+            id: T::DiscordId,
+        ) -> DispatchResult {
+            let action = Call::<T>::confirm_role_id { guild, role, id };
+
+            Roles::<T>::try_mutate(guild, role, |stored_role| -> DispatchResult {
+                if Self::ensure_bot(origin, guild, BotCapabilities::MANAGE_ROLES, &action)?
+                    .is_none()
+                {
+                    return Ok(());
+                }
+
+                let stored_role = stored_role.as_mut().ok_or(Error::<T>::RoleDoesntExist)?;
+
+                ensure!(stored_role.id.is_none(), Error::<T>::RoleIdAlreadyConfirmed);
+                ensure!(
+                    !RoleIdToIndex::<T>::contains_key(guild, id),
+                    Error::<T>::RoleIdAlreadyInUse
+                );
+
+                stored_role.id = Some(id);
+                RoleIdToIndex::<T>::insert(guild, id, role);
+
+                Self::deposit_event(Event::RoleIdConfirmed(guild, role, id));
+
+                Ok(())
+            })
+        }
+
+        #[pallet::weight(
+            T::WeightInfo::assign_role()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn assign_role(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            target: T::AccountId,
+            role: RoleRef<T::DiscordId>,
+        ) -> DispatchResult {
+            let action = Call::<T>::assign_role {
+                guild,
+                target: target.clone(),
+                role,
+            };
+
+            GuildMembers::<T>::try_mutate(guild, target.clone(), |guild_member| -> DispatchResult {
+                let actor = match Self::ensure_bot_or_permission(
+                    origin,
+                    guild,
+                    BotCapabilities::MANAGE_ROLES,
+                    Permissions::MANAGE_ROLES,
+                    &action,
+                )? {
+                    Some(actor) => actor,
+                    None => return Ok(()),
+                };
+
+                let (index, role) = Self::resolve_role(guild, role)?;
+
+                Self::ensure_above_role(guild, &actor, role.position)?;
+                Self::ensure_holds_permissions(guild, &actor, role.permissions)?;
+
+                let mut old_member = guild_member
+                    .take()
+                    .ok_or(Error::<T>::NotAMemberOfTheGuild)?;
+
+                ensure!(
+                    !old_member.roles.contains(&index),
+                    Error::<T>::RoleAlreadyAssigned
+                );
+
+                old_member
+                    .roles
+                    .try_push(index)
+                    .map_err(|_| Error::<T>::TooManyRoles)?;
+
+                *guild_member = Some(old_member);
+
+                Self::deposit_event(Event::RoleAssigned(guild, target, index));
+
+                Ok(())
+            })
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        #[pallet::weight(
+            T::WeightInfo::update_role()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn update_role(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            role: RoleRef<T::DiscordId>,
+            name: Vec<u8>,
+            color: u64,
+            hoist: bool,
+            position: u64,
+            permissions: PermissionSet,
+            mentionable: bool,
+        ) -> DispatchResult {
+            let action = Call::<T>::update_role {
+                guild,
+                role,
+                name: name.clone(),
+                color,
+                hoist,
+                position,
+                permissions,
+                mentionable,
+            };
+
+            let actor = match Self::ensure_bot_or_permission(
+                origin,
+                guild,
+                BotCapabilities::MANAGE_ROLES,
+                Permissions::MANAGE_ROLES,
+                &action,
+            )? {
+                Some(actor) => actor,
+                None => return Ok(()),
+            };
+
+            let (index, mut stored_role) = Self::resolve_role(guild, role)?;
+
+            Self::ensure_above_role(guild, &actor, stored_role.position)?;
+            Self::ensure_above_role(guild, &actor, position)?;
+            Self::ensure_holds_permissions(guild, &actor, permissions)?;
+
+            let bounded_name: NameOf<T> = name
+                .clone()
+                .try_into()
+                .map_err(|_| Error::<T>::NameTooLong)?;
+
+            stored_role.name = bounded_name;
+            stored_role.color = color;
+            stored_role.hoist = hoist;
+            stored_role.position = position;
+            stored_role.permissions = permissions;
+            stored_role.mentionable = mentionable;
+
+            Roles::<T>::insert(guild, index, stored_role);
+
+            Self::deposit_event(Event::RoleUpdated(
+                guild,
+                index,
+                name,
+                color,
+                hoist,
+                position,
+                permissions,
+                mentionable,
+            ));
+
+            Ok(())
+        }
+
+        /// `witness` must be at least the number of members, banned members and channels of the
+        /// guild, as all of them are scanned for references to the role.
+        #[pallet::weight(
+            T::WeightInfo::delete_role(*witness)
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn delete_role(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            role: RoleRef<T::DiscordId>,
+            witness: u32,
+        ) -> DispatchResult {
+            let action = Call::<T>::delete_role {
+                guild,
+                role,
+                witness,
+            };
+
+            let actor = match Self::ensure_bot_or_permission(
+                origin,
+                guild,
+                BotCapabilities::MANAGE_ROLES,
+                Permissions::MANAGE_ROLES,
+                &action,
+            )? {
+                Some(actor) => actor,
+                None => return Ok(()),
+            };
+
+            let (index, stored_role) = Self::resolve_role(guild, role)?;
+
+            Self::ensure_above_role(guild, &actor, stored_role.position)?;
+
+            Self::remove_role_references(guild, index, witness)?;
+
+            Roles::<T>::remove(guild, index);
+
+            if let Some(id) = stored_role.id {
+                RoleIdToIndex::<T>::remove(guild, id);
+            }
+
+            Self::release_deposit(guild, DepositKey::Role(index));
+
+            Self::deposit_event(Event::RoleDeleted(guild, index));
+
+            Ok(())
+        }
+
+        #[pallet::weight(
+            T::WeightInfo::revoke_role()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn revoke_role(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            target: T::AccountId,
+            role: RoleRef<T::DiscordId>,
+        ) -> DispatchResult {
+            let action = Call::<T>::revoke_role {
+                guild,
+                target: target.clone(),
+                role,
+            };
+
+            GuildMembers::<T>::try_mutate(guild, target.clone(), |guild_member| -> DispatchResult {
+                let actor = match Self::ensure_bot_or_permission(
+                    origin,
+                    guild,
+                    BotCapabilities::MANAGE_ROLES,
+                    Permissions::MANAGE_ROLES,
+                    &action,
+                )? {
+                    Some(actor) => actor,
+                    None => return Ok(()),
+                };
+
+                let (index, role) = Self::resolve_role(guild, role)?;
+
+                Self::ensure_above_role(guild, &actor, role.position)?;
+
+                let guild_member = guild_member
+                    .as_mut()
+                    .ok_or(Error::<T>::NotAMemberOfTheGuild)?;
+
+                let old_len = guild_member.roles.len();
+                guild_member.roles.retain(|role| *role != index);
+
+                ensure!(
+                    guild_member.roles.len() < old_len,
+                    Error::<T>::RoleNotAssigned
+                );
+
+                Self::end_subscription(guild, &target, index);
+
+                Self::deposit_event(Event::RoleRevoked(guild, target, index));
+
+                Ok(())
+            })
+        }
+
+        #[pallet::weight(
+            T::WeightInfo::create_channel(permission_overwrites.len() as u32)
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn create_channel(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            channel_id: T::DiscordId,
+            channel_type: ChannelType,
+            position: u64,
+            permission_overwrites: PermissionOverwrites<T>,
+        ) -> DispatchResult {
+            let action = Call::<T>::create_channel {
+                guild,
+                channel_id,
+                channel_type,
+                position,
+                permission_overwrites: permission_overwrites.clone(),
+            };
+
+            Channels::<T>::try_mutate(guild, channel_id, |channel| -> DispatchResult {
+                if Self::ensure_bot(origin, guild, BotCapabilities::MANAGE_CHANNELS, &action)?
+                    .is_none()
+                {
+                    return Ok(());
+                }
+
+                ensure!(channel.is_none(), Error::<T>::ChannelAlreadyExists);
+
+                *channel = Some(Channel {
+                    id: channel_id,
+                    channel_type,
+                    position,
+                    permission_overwrites: permission_overwrites
+                        .try_into()
+                        .map_err(|_| Error::<T>::TooManyOverwrites)?,
+                });
+
+                Self::deposit_event(Event::ChannelCreated(
+                    guild,
+                    channel_id,
+                    channel_type,
+                    position,
+                ));
+
+                Ok(())
+            })
+        }
+
+        #[pallet::weight(
+            T::WeightInfo::update_channel(permission_overwrites.len() as u32)
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn update_channel(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            channel_id: T::DiscordId,
+            channel_type: ChannelType,
+            position: u64,
+            permission_overwrites: PermissionOverwrites<T>,
+        ) -> DispatchResult {
+            let action = Call::<T>::update_channel {
+                guild,
+                channel_id,
+                channel_type,
+                position,
+                permission_overwrites: permission_overwrites.clone(),
+            };
+
+            Channels::<T>::try_mutate(guild, channel_id, |channel| -> DispatchResult {
+                if Self::ensure_bot(origin, guild, BotCapabilities::MANAGE_CHANNELS, &action)?
+                    .is_none()
+                {
+                    return Ok(());
+                }
+
+                let channel = channel.as_mut().ok_or(Error::<T>::ChannelDoesntExist)?;
+
+                channel.channel_type = channel_type;
+                channel.position = position;
+                channel.permission_overwrites = permission_overwrites
+                    .try_into()
+                    .map_err(|_| Error::<T>::TooManyOverwrites)?;
+
+                Self::deposit_event(Event::ChannelUpdated(
+                    guild,
+                    channel_id,
+                    channel_type,
+                    position,
+                ));
+
+                Ok(())
+            })
+        }
+
+        #[pallet::weight(
+            T::WeightInfo::delete_channel()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn delete_channel(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            channel_id: T::DiscordId,
+        ) -> DispatchResult {
+            let action = Call::<T>::delete_channel { guild, channel_id };
+
+            if Self::ensure_bot(origin, guild, BotCapabilities::MANAGE_CHANNELS, &action)?.is_none()
+            {
+                return Ok(());
+            }
+
+            ensure!(
+                Channels::<T>::take(guild, channel_id).is_some(),
+                Error::<T>::ChannelDoesntExist
+            );
+
+            Self::deposit_event(Event::ChannelDeleted(guild, channel_id));
+
+            Ok(())
+        }
+
+        /// Links `account` to `discord_user`. The bot attests to the Discord user while the
+        /// signature, made by `account` over [`Pallet::link_challenge`], proves the member owns
+        /// the account. Links are shared by every guild, so a Discord user already linked to
+        /// another account must be unlinked by that account first.
+        #[pallet::weight(
+            T::WeightInfo::link_account()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn link_account(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            account: T::AccountId,
+            discord_user: T::DiscordId,
+            signature: T::Signature,
+        ) -> DispatchResult {
+            let action = Call::<T>::link_account {
+                guild,
+                account: account.clone(),
+                discord_user,
+                signature: signature.clone(),
+            };
+
+            if Self::ensure_bot(origin, guild, BotCapabilities::LINK_ACCOUNTS, &action)?.is_none() {
+                return Ok(());
+            }
+
+            let challenge = Self::link_challenge(&account, discord_user);
+            ensure!(
+                signature.verify(&challenge[..], &account),
+                Error::<T>::InvalidLinkSignature
+            );
+
+            let previous_user = DiscordUserOf::<T>::get(&account);
+            ensure!(
+                previous_user != Some(discord_user),
+                Error::<T>::AlreadyLinked
+            );
+            ensure!(
+                !AccountOfDiscordUser::<T>::contains_key(discord_user),
+                Error::<T>::DiscordUserAlreadyLinked
+            );
+
+            if let Some(previous_user) = previous_user {
+                AccountOfDiscordUser::<T>::remove(previous_user);
+            }
+
+            DiscordUserOf::<T>::insert(&account, discord_user);
+            AccountOfDiscordUser::<T>::insert(discord_user, &account);
+            LinkNonce::<T>::mutate(&account, |nonce| *nonce = nonce.wrapping_add(1));
+
+            match previous_user {
+                Some(previous_user) => Self::deposit_event(Event::AccountRelinked(
+                    account,
+                    previous_user,
+                    discord_user,
+                )),
+                None => Self::deposit_event(Event::AccountLinked(account, discord_user)),
+            }
+
+            Ok(())
+        }
+
+        /// Removes the link between the signing account and its Discord user.
+        #[pallet::weight(T::WeightInfo::unlink_account())]
+        pub fn unlink_account(origin: OriginFor<T>) -> DispatchResult {
+            let account = ensure_signed(origin)?;
+
+            let discord_user =
+                DiscordUserOf::<T>::take(&account).ok_or(Error::<T>::AccountNotLinked)?;
+            AccountOfDiscordUser::<T>::remove(discord_user);
+
+            Self::deposit_event(Event::AccountUnlinked(account, discord_user));
+
+            Ok(())
+        }
+
+        /// Grants `role` to members whose free balance is at least `min_balance`, and revokes it
+        /// from those below, or removes the gate if `min_balance` is `None`. Members are
+        /// re-evaluated on their next check or `refresh_token_gates`.
+        ///
+        /// This call doesn't visit the guild's members. Those added while the guild had no gate
+        /// aren't enrolled in the periodic checks, so after setting a guild's first gate the bot
+        /// calls `refresh_token_gates` once for each of them.
+        #[pallet::weight(
+            T::WeightInfo::set_token_gate()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn set_token_gate(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            role: RoleRef<T::DiscordId>,
+            min_balance: Option<BalanceOf<T>>,
+        ) -> DispatchResult {
+            let action = Call::<T>::set_token_gate {
+                guild,
+                role,
+                min_balance,
+            };
+
+            let actor = match Self::ensure_bot_or_permission(
+                origin,
+                guild,
+                BotCapabilities::MANAGE_ROLES,
+                Permissions::MANAGE_ROLES,
+                &action,
+            )? {
+                Some(actor) => actor,
+                None => return Ok(()),
+            };
+
+            let (index, stored_role) = Self::resolve_role(guild, role)?;
+
+            Self::ensure_above_role(guild, &actor, stored_role.position)?;
+            Self::ensure_holds_permissions(guild, &actor, stored_role.permissions)?;
+
+            TokenGates::<T>::try_mutate(guild, |gates| -> DispatchResult {
+                gates.retain(|(gated, _)| *gated != index);
+
+                if let Some(min_balance) = min_balance {
+                    gates
+                        .try_push((index, min_balance))
+                        .map_err(|_| Error::<T>::TooManyTokenGates)?;
+                }
+
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::TokenGateSet(guild, index, min_balance));
+
+            Ok(())
+        }
+
+        /// Grants or revokes the token-gated roles of `member` according to their current free
+        /// balance. From then on they are re-evaluated every `TokenGateCheckPeriod` blocks while
+        /// they remain in the guild.
+        #[pallet::weight(T::WeightInfo::refresh_token_gates())]
+        pub fn refresh_token_gates(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            member: T::AccountId,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            ensure!(
+                GuildMembers::<T>::contains_key(guild, &member),
+                Error::<T>::NotAMemberOfTheGuild
+            );
+
+            Self::enroll_in_token_gate_checks(guild, &member)?;
+            Self::apply_token_gates(guild, &member);
+
+            Ok(())
+        }
+
+        /// Lets members subscribe to `role` under `plan`, or stops new subscriptions if `plan` is
+        /// `None`. Existing subscriptions renew at the price of the current plan and expire once
+        /// the plan is removed.
+        #[pallet::weight(
+            T::WeightInfo::set_subscription_plan()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn set_subscription_plan(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            role: RoleRef<T::DiscordId>,
+            plan: Option<SubscriptionPlanOf<T>>,
+        ) -> DispatchResult {
+            let action = Call::<T>::set_subscription_plan {
+                guild,
+                role,
+                plan: plan.clone(),
+            };
+
+            let actor = match Self::ensure_bot_or_permission(
+                origin,
+                guild,
+                BotCapabilities::MANAGE_ROLES,
+                Permissions::MANAGE_ROLES,
+                &action,
+            )? {
+                Some(actor) => actor,
+                None => return Ok(()),
+            };
+
+            let (index, stored_role) = Self::resolve_role(guild, role)?;
+
+            Self::ensure_above_role(guild, &actor, stored_role.position)?;
+            Self::ensure_holds_permissions(guild, &actor, stored_role.permissions)?;
+
+            if let Some(plan) = &plan {
+                ensure!(
+                    !plan.period.is_zero() && plan.grace_period < plan.period,
+                    Error::<T>::InvalidSubscriptionPlan
+                );
+            }
+
+            SubscriptionPlans::<T>::set(guild, index, plan.clone());
+
+            Self::deposit_event(Event::SubscriptionPlanSet(guild, index, plan));
+
+            Ok(())
+        }
+
+        /// Pays for a period of `role` and grants it to the signing member. The fee goes to the
+        /// guild's account and is charged again at the end of every period until cancelled.
+        #[pallet::weight(T::WeightInfo::subscribe())]
+        #[transactional]
+        pub fn subscribe(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            role: RoleRef<T::DiscordId>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (index, _) = Self::resolve_role(guild, role)?;
+            let plan =
+                SubscriptionPlans::<T>::get(guild, index).ok_or(Error::<T>::NoSubscriptionPlan)?;
+
+            ensure!(
+                !Subscriptions::<T>::contains_key(guild, (&who, index)),
+                Error::<T>::AlreadySubscribed
+            );
+
+            let mut guild_member =
+                GuildMembers::<T>::get(guild, &who).ok_or(Error::<T>::NotAMemberOfTheGuild)?;
+
+            ensure!(
+                !guild_member.roles.contains(&index),
+                Error::<T>::RoleAlreadyAssigned
+            );
+
+            guild_member
+                .roles
+                .try_push(index)
+                .map_err(|_| Error::<T>::TooManyRoles)?;
+
+            T::Currency::transfer(
+                &who,
+                &Self::guild_account(guild),
+                plan.price,
+                ExistenceRequirement::KeepAlive,
+            )?;
+
+            let paid_until = frame_system::Pallet::<T>::block_number().saturating_add(plan.period);
+            Self::schedule(
+                paid_until,
+                ScheduledTask::SubscriptionRenewal(guild, who.clone(), index),
+            )?;
+
+            GuildMembers::<T>::insert(guild, &who, guild_member);
+            Subscriptions::<T>::insert(
+                guild,
+                (&who, index),
+                Subscription {
+                    paid_until,
+                    auto_renew: true,
+                },
+            );
+
+            Self::deposit_event(Event::Subscribed(guild, who, index, paid_until));
+
+            Ok(())
+        }
+
+        /// Stops renewing the signing member's subscription to `role`. They keep the role until
+        /// the end of the paid period.
+        #[pallet::weight(T::WeightInfo::cancel_subscription())]
+        pub fn cancel_subscription(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            role: RoleRef<T::DiscordId>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (role, _) = Self::resolve_role(guild, role)?;
+
+            Subscriptions::<T>::try_mutate(
+                guild,
+                (&who, role),
+                |subscription| -> DispatchResult {
+                    let subscription = subscription.as_mut().ok_or(Error::<T>::NotSubscribed)?;
+                    subscription.auto_renew = false;
+
+                    Ok(())
+                },
+            )?;
+
+            Self::deposit_event(Event::SubscriptionCancelled(guild, who, role));
+
+            Ok(())
+        }
+
+        /// Lets members holding `role`, besides those holding `MANAGE_GUILD`, propose and approve
+        /// spends from the guild's treasury, and sets how many approvals a spend needs, at least
+        /// 2.
+        #[pallet::weight(
+            T::WeightInfo::set_treasury_approvers()
+                .saturating_add(T::WeightInfo::approve_bot_action())
+        )]
+        #[transactional]
+        pub fn set_treasury_approvers(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            role: Option<RoleRef<T::DiscordId>>,
+            threshold: u32,
+        ) -> DispatchResult {
+            let action = Call::<T>::set_treasury_approvers {
+                guild,
+                role,
+                threshold,
+            };
+
+            if Self::ensure_bot_or_permission(
+                origin,
+                guild,
+                BotCapabilities::MANAGE_GUILD,
+                Permissions::MANAGE_GUILD,
+                &action,
+            )?
+            .is_none()
+            {
+                return Ok(());
+            }
+
+            ensure!(threshold >= 2, Error::<T>::ThresholdTooLow);
+            ensure!(
+                threshold <= T::MaxSpendApprovals::get(),
+                Error::<T>::ThresholdTooHigh
+            );
+
+            let role = match role {
+                Some(role) => Some(Self::resolve_role(guild, role)?.0),
+                None => None,
+            };
+
+            TreasuryRole::<T>::set(guild, role);
+            SpendThreshold::<T>::insert(guild, threshold);
+
+            Self::deposit_event(Event::TreasuryApproversSet(guild, role, threshold));
+
+            Ok(())
+        }
+
+        /// Proposes paying `amount` out of the guild's treasury to `beneficiary`. The proposal
+        /// counts as the proposer's approval.
+        #[pallet::weight(T::WeightInfo::propose_spend())]
+        #[transactional]
+        pub fn propose_spend(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            beneficiary: T::AccountId,
+            amount: BalanceOf<T>,
+            description: Vec<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                Self::can_approve_spends(guild, &who),
+                Error::<T>::NoPermission
+            );
+
+            let description: ReasonOf<T> = description
+                .try_into()
+                .map_err(|_| Error::<T>::ReasonTooLong)?;
+
+            let index = NextSpendIndex::<T>::get(guild);
+            NextSpendIndex::<T>::insert(
+                guild,
+                index.checked_add(1).ok_or(Error::<T>::StorageOverflow)?,
+            );
+
+            SpendProposals::<T>::insert(
+                guild,
+                index,
+                SpendProposal {
+                    proposer: who.clone(),
+                    beneficiary: beneficiary.clone(),
+                    amount,
+                    description,
+                    approvals: BoundedVec::default(),
+                },
+            );
+
+            Self::deposit_event(Event::SpendProposed(
+                guild,
+                index,
+                who.clone(),
+                beneficiary,
+                amount,
+            ));
+
+            Self::approve_spend_as(guild, index, who)
+        }
+
+        /// Approves a spend, paying it out once it has as many approvals as the guild requires.
+        /// The approval paying it out fails while the treasury can't afford it.
+        #[pallet::weight(T::WeightInfo::approve_spend())]
+        #[transactional]
+        pub fn approve_spend(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            index: SpendIndex,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                Self::can_approve_spends(guild, &who),
+                Error::<T>::NoPermission
+            );
+
+            Self::approve_spend_as(guild, index, who)
+        }
+
+        /// Withdraws a spend proposal. Only its proposer or members holding `MANAGE_GUILD` can
+        /// do so.
+        #[pallet::weight(T::WeightInfo::cancel_spend())]
+        pub fn cancel_spend(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            index: SpendIndex,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let proposal =
+                SpendProposals::<T>::get(guild, index).ok_or(Error::<T>::UnknownSpend)?;
+
+            ensure!(
+                proposal.proposer == who
+                    || Self::has_permission(guild, &who, None, Permissions::MANAGE_GUILD),
+                Error::<T>::NoPermission
+            );
+
+            SpendProposals::<T>::remove(guild, index);
+
+            Self::deposit_event(Event::SpendCancelled(guild, index));
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Ensures the origin is signed by one of the bots of `guild` allowed to use `capability`.
+        ///
+        /// Returns `None` while `action` waits for more bots to submit it, in which case the call
+        /// returns without acting.
+        fn ensure_bot(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            capability: BotCapabilities,
+            action: &Call<T>,
+        ) -> Result<Option<T::AccountId>, DispatchError> {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                Self::bot_has_capability(guild, &who, capability),
+                Error::<T>::NoPermission
+            );
+
+            match Self::approve_bot_action(guild, &who, capability, action)? {
+                true => Ok(Some(who)),
+                false => Ok(None),
+            }
+        }
+
+        /// Records `bot` submitting `action`, returning whether enough bots of the guild did so
+        /// for it to run.
+        pub(crate) fn approve_bot_action(
+            guild: T::DiscordId,
+            bot: &T::AccountId,
+            capability: BotCapabilities,
+            action: &Call<T>,
+        ) -> Result<bool, DispatchError> {
+            let threshold = BotThreshold::<T>::get(guild);
+
+            if threshold <= 1 {
+                return Ok(true);
+            }
+
+            let hash = T::Hashing::hash_of(action);
+            let now = frame_system::Pallet::<T>::block_number();
+
+            // Expired approvals are dropped when the action is submitted again, or when one of
+            // the bots that submitted it opens another proposal.
+            let mut proposal = match BotProposals::<T>::get(guild, hash) {
+                Some(proposal) if proposal.expires_at > now => proposal,
+                expired => {
+                    if expired.is_some() {
+                        BotProposals::<T>::remove(guild, hash);
+
+                        Self::deposit_event(Event::BotProposalExpired(guild, hash));
+                    }
+
+                    BotProposal {
+                        approvals: BoundedVec::default(),
+                        expires_at: now.saturating_add(T::BotProposalLifetime::get()),
+                    }
+                }
+            };
+
+            // Bots unregistered or stripped of the capability since submitting no longer count.
+            proposal
+                .approvals
+                .retain(|approval| Self::bot_has_capability(guild, approval, capability));
+
+            ensure!(
+                !proposal.approvals.contains(bot),
+                Error::<T>::AlreadyApproved
+            );
+
+            if proposal.approvals.len() as u32 + 1 >= threshold {
+                BotProposals::<T>::remove(guild, hash);
+
+                return Ok(true);
+            }
+
+            Self::open_bot_proposal(guild, bot, hash, now)?;
+            proposal
+                .approvals
+                .try_push(bot.clone())
+                .map_err(|_| Error::<T>::StorageOverflow)?;
+            let count = proposal.approvals.len() as u32;
+            BotProposals::<T>::insert(guild, hash, proposal);
+
+            Self::deposit_event(Event::BotActionApproved(guild, bot.clone(), hash, count));
+
+            Ok(false)
+        }
+
+        /// Adds `hash` to the actions `bot` has waiting for the threshold, after dropping the ones
+        /// that ran, expired or no longer count the bot. Expired proposals are removed from
+        /// storage on the way, so they don't outlive the bots that opened them.
+        fn open_bot_proposal(
+            guild: T::DiscordId,
+            bot: &T::AccountId,
+            hash: T::Hash,
+            now: T::BlockNumber,
+        ) -> DispatchResult {
+            OpenBotProposals::<T>::try_mutate(guild, bot, |open| {
+                open.retain(|open_hash| match BotProposals::<T>::get(guild, open_hash) {
+                    Some(proposal) if proposal.expires_at <= now => {
+                        BotProposals::<T>::remove(guild, open_hash);
+
+                        Self::deposit_event(Event::BotProposalExpired(guild, *open_hash));
+
+                        false
+                    }
+                    Some(proposal) => proposal.approvals.contains(bot),
+                    None => false,
+                });
+
+                open.try_push(hash)
+                    .map_err(|_| Error::<T>::TooManyBotProposals.into())
+            })
+        }
+
+        /// Whether `account` is a bot of `guild` allowed to use `capability`.
+        pub fn bot_has_capability(
+            guild: T::DiscordId,
+            account: &T::AccountId,
+            capability: BotCapabilities,
+        ) -> bool {
+            Bots::<T>::get(guild, account)
+                .map_or(false, |bot| bot.capabilities.contains(capability))
+        }
+
+        /// The bots of `guild` along with their metadata.
+        pub fn bots(guild: T::DiscordId) -> Vec<(T::AccountId, BotInfo<T::DiscordId, Vec<u8>>)> {
+            Bots::<T>::iter_prefix(guild)
+                .map(|(account, bot)| {
+                    (
+                        account,
+                        BotInfo {
+                            name: bot.name.into_inner(),
+                            application_id: bot.application_id,
+                            capabilities: bot.capabilities,
+                        },
+                    )
+                })
+                .collect()
+        }
+
+        /// The message `account` must sign to be linked to `discord_user`.
+        pub fn link_challenge(account: &T::AccountId, discord_user: T::DiscordId) -> Vec<u8> {
+            (
+                LINK_CHALLENGE_PREFIX,
+                account,
+                discord_user,
+                LinkNonce::<T>::get(account),
+            )
+                .encode()
+        }
+
+        /// Ensures the origin is either one of the guild's bots allowed to use `capability` or a
+        /// member holding `permission`.
+        ///
+        /// Like `ensure_bot`, returns `None` while a bot's `action` waits for more bots
+        /// to submit it. Members act on their own.
+        fn ensure_bot_or_permission(
+            origin: OriginFor<T>,
+            guild: T::DiscordId,
+            capability: BotCapabilities,
+            permission: Permissions,
+            action: &Call<T>,
+        ) -> Result<Option<Actor<T::AccountId>>, DispatchError> {
+            let who = ensure_signed(origin)?;
+
+            if Self::bot_has_capability(guild, &who, capability) {
+                return match Self::approve_bot_action(guild, &who, capability, action)? {
+                    true => Ok(Some(Actor::Bot(who))),
+                    false => Ok(None),
+                };
+            }
+
+            ensure!(
+                Self::has_permission(guild, &who, None, permission),
+                Error::<T>::NoPermission
+            );
+
+            Ok(Some(Actor::Member(who)))
+        }
+
+        /// Position of the highest role `member` holds in `guild`, @everyone sitting at 0.
+        pub fn highest_role_position(guild: T::DiscordId, member: &T::AccountId) -> u64 {
+            GuildMembers::<T>::get(guild, member)
+                .map(|guild_member| {
+                    guild_member
+                        .roles
+                        .iter()
+                        .filter_map(|role| Roles::<T>::get(guild, role))
+                        .map(|role| role.position)
+                        .max()
+                        .unwrap_or_default()
+                })
+                .unwrap_or_default()
+        }
+
+        /// Ensures a member acting on a role at `position` holds a role strictly above it. Bots
+        /// mirror Discord, which already enforced the hierarchy, so they aren't restricted.
+        fn ensure_above_role(
+            guild: T::DiscordId,
+            actor: &Actor<T::AccountId>,
+            position: u64,
+        ) -> DispatchResult {
+            if let Actor::Member(who) = actor {
+                ensure!(
+                    Self::highest_role_position(guild, who) > position,
+                    Error::<T>::RoleAboveActor
+                );
+            }
+
+            Ok(())
+        }
+
+        /// Ensures a member granting `permissions`, directly or through a role others can pick
+        /// up, holds all of them, so `MANAGE_ROLES` can't be escalated into `ADMINISTRATOR`.
+        fn ensure_holds_permissions(
+            guild: T::DiscordId,
+            actor: &Actor<T::AccountId>,
+            permissions: PermissionSet,
+        ) -> DispatchResult {
+            if let Actor::Member(who) = actor {
+                let held = Self::effective_permissions(guild, who, None);
+
+                ensure!(
+                    held.contains(PermissionSet::ADMINISTRATOR) || held.contains(permissions),
+                    Error::<T>::PermissionsAboveActor
+                );
+            }
+
+            Ok(())
+        }
+
+        /// Bounds the names of a guild submitted by a caller.
+        fn bound_guild(guild: GuildInputOf<T>) -> Result<GuildOf<T>, DispatchError> {
+            Ok(Guild {
+                id: guild.id,
+                name: guild.name.try_into().map_err(|_| Error::<T>::NameTooLong)?,
+                icon: guild.icon.try_into().map_err(|_| Error::<T>::NameTooLong)?,
+                owner_id: guild.owner_id,
+                afk_channel_id: guild.afk_channel_id,
+                afk_timeout: guild.afk_timeout,
+                verification_level: guild.verification_level,
+                default_message_notifications: guild.default_message_notifications,
+                explicit_content_filter: guild.explicit_content_filter,
+                rules_channel_id: guild.rules_channel_id,
+                system_channel_id: guild.system_channel_id,
+            })
+        }
+
+        /// Schedules `task` to run at block `at`, or at one of the following blocks if `at`
+        /// already has the maximum number of tasks.
+        fn schedule(at: T::BlockNumber, task: ScheduledTaskOf<T>) -> DispatchResult {
+            Self::schedule_within(at, task, SCHEDULE_LOOKAHEAD)
+        }
+
+        /// Schedules `task` to run at the first of the `lookahead` blocks from `at` with room for
+        /// it.
+        fn schedule_within(
+            mut at: T::BlockNumber,
+            task: ScheduledTaskOf<T>,
+            lookahead: u32,
+        ) -> DispatchResult {
+            for _ in 0..lookahead {
+                if Scheduled::<T>::try_mutate(at, |tasks| tasks.try_push(task.clone())).is_ok() {
+                    return Ok(());
+                }
+
+                at = at.saturating_add(One::one());
+            }
+
+            Err(Error::<T>::SchedulerFull.into())
+        }
+
+        /// Runs a scheduled task. Tasks are checked against the current state, so ones made
+        /// obsolete since they were scheduled do nothing.
+        fn run_task(now: T::BlockNumber, task: ScheduledTaskOf<T>) {
+            match task {
+                ScheduledTask::BanExpiry(guild, account) => {
+                    let expired = BannedMembers::<T>::get(guild, &account)
+                        .and_then(|(_, _, expires_at)| expires_at)
+                        .map_or(false, |expires_at| expires_at <= now);
+
+                    if expired {
+                        BannedMembers::<T>::remove(guild, &account);
+                        Self::release_deposit(guild, DepositKey::Ban(account.clone()));
+                        Self::release_deposit(guild, DepositKey::Member(account.clone()));
+
+                        Self::deposit_event(Event::BanExpired(guild, account));
+                    }
+                }
+                ScheduledTask::TimeoutExpiry(guild, account) => {
+                    GuildMembers::<T>::mutate(guild, &account, |guild_member| {
+                        let guild_member = match guild_member {
+                            Some(guild_member) => guild_member,
+                            None => return,
+                        };
+
+                        if guild_member
+                            .communication_disabled_until
+                            .map_or(false, |until| until <= now)
+                        {
+                            guild_member.communication_disabled_until = None;
+
+                            Self::deposit_event(Event::TimeoutExpired(guild, account.clone()));
+                        }
+                    })
+                }
+                ScheduledTask::TokenGateCheck(guild, account) => {
+                    let gated = GuildMembers::<T>::contains_key(guild, &account)
+                        && !TokenGates::<T>::get(guild).is_empty();
+
+                    // The member is evaluated even if the next check can't be scheduled.
+                    if gated {
+                        Self::apply_token_gates(guild, &account);
+                    }
+
+                    if !gated {
+                        TokenGateChecks::<T>::remove(guild, &account);
+                    } else if Self::schedule_token_gate_check(guild, account.clone()).is_err() {
+                        // A later `refresh_token_gates` starts the checks again.
+                        TokenGateChecks::<T>::remove(guild, &account);
+
+                        Self::deposit_event(Event::TokenGateCheckNotScheduled(guild, account));
+                    }
+                }
+                ScheduledTask::SubscriptionRenewal(guild, account, role) => {
+                    Self::renew_subscription(now, guild, account, role)
+                }
+            }
+        }
+
+        /// The treasury account of `guild`, receiving subscription fees and paying approved
+        /// spends.
+        pub fn guild_account(guild: T::DiscordId) -> T::AccountId {
+            T::PalletId::get().into_sub_account(guild)
+        }
+
+        /// Whether `member` can propose and approve spends from the treasury of `guild`.
+        pub fn can_approve_spends(guild: T::DiscordId, member: &T::AccountId) -> bool {
+            let holds_treasury_role = TreasuryRole::<T>::get(guild).map_or(false, |role| {
+                GuildMembers::<T>::get(guild, member)
+                    .map_or(false, |guild_member| guild_member.roles.contains(&role))
+            });
+
+            holds_treasury_role
+                || Self::has_permission(guild, member, None, Permissions::MANAGE_GUILD)
+        }
+
+        /// Records `approver` approving spend `index`, and pays it out if that's enough
+        /// approvals.
+        fn approve_spend_as(
+            guild: T::DiscordId,
+            index: SpendIndex,
+            approver: T::AccountId,
+        ) -> DispatchResult {
+            let mut proposal =
+                SpendProposals::<T>::get(guild, index).ok_or(Error::<T>::UnknownSpend)?;
+
+            // Members who lost their role or permission since no longer count.
+            proposal
+                .approvals
+                .retain(|account| Self::can_approve_spends(guild, account));
+
+            ensure!(
+                !proposal.approvals.contains(&approver),
+                Error::<T>::AlreadyApproved
+            );
+
+            proposal
+                .approvals
+                .try_push(approver.clone())
+                .map_err(|_| Error::<T>::StorageOverflow)?;
+
+            let approvals = proposal.approvals.len() as u32;
+
+            Self::deposit_event(Event::SpendApproved(guild, index, approver, approvals));
+
+            if approvals < SpendThreshold::<T>::get(guild) {
+                SpendProposals::<T>::insert(guild, index, proposal);
+
+                return Ok(());
+            }
+
+            SpendProposals::<T>::remove(guild, index);
+
+            T::Currency::transfer(
+                &Self::guild_account(guild),
+                &proposal.beneficiary,
+                proposal.amount,
+                ExistenceRequirement::KeepAlive,
+            )?;
+
+            SpendHistory::<T>::mutate(guild, |history| {
+                if !history.is_empty() && history.len() as u32 >= T::MaxSpendHistory::get() {
+                    history.remove(0);
+                }
+
+                // Only fails when no history is kept.
+                let _ = history.try_push(Spend {
+                    index,
+                    beneficiary: proposal.beneficiary.clone(),
+                    amount: proposal.amount,
+                    paid_at: frame_system::Pallet::<T>::block_number(),
+                });
+            });
+
+            Self::deposit_event(Event::SpendPaid(
+                guild,
+                index,
+                proposal.beneficiary,
+                proposal.amount,
+            ));
+
+            Ok(())
+        }
+
+        /// The account paying the deposits of the items `actor` writes. Bots pay for their own
+        /// writes, while members acting through their permissions do so on the guild's behalf.
+        fn depositor(guild: T::DiscordId, actor: &Actor<T::AccountId>) -> T::AccountId {
+            match actor {
+                Actor::Bot(bot) => bot.clone(),
+                Actor::Member(_) => Self::guild_account(guild),
+            }
+        }
+
+        /// Reserves `amount` from `depositor` for `item`, until `release_deposit` is called for
+        /// it.
+        fn reserve_deposit(
+            guild: T::DiscordId,
+            item: DepositKey<T::AccountId>,
+            depositor: T::AccountId,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            if amount.is_zero() {
+                return Ok(());
+            }
+
+            T::Currency::reserve(&depositor, amount)?;
+            Deposits::<T>::insert(guild, item, (depositor, amount));
+
+            Ok(())
+        }
+
+        /// Returns the deposit held for `item`, if any, to whoever paid it.
+        fn release_deposit(guild: T::DiscordId, item: DepositKey<T::AccountId>) {
+            if let Some((depositor, amount)) = Deposits::<T>::take(guild, item) {
+                T::Currency::unreserve(&depositor, amount);
+            }
+        }
+
+        /// Charges a subscription at the end of its paid period. When the fee can't be paid the
+        /// member keeps the role for the plan's grace period, after which it's charged one last
+        /// time. Subscriptions that are cancelled, lost their plan or role, or can't be paid
+        /// expire and their role is revoked, as do those whose next renewal finds no room within
+        /// `RENEWAL_LOOKAHEAD` blocks, without being charged.
+        fn renew_subscription(
+            now: T::BlockNumber,
+            guild: T::DiscordId,
+            account: T::AccountId,
+            role: RoleIndex,
+        ) {
+            let key = (account.clone(), role);
+
+            // Tasks of subscriptions that were renewed or replaced since are stale.
+            let subscription = match Subscriptions::<T>::get(guild, &key) {
+                Some(subscription) if subscription.paid_until <= now => subscription,
+                _ => return,
+            };
+
+            let holds_role = GuildMembers::<T>::get(guild, &account)
+                .map_or(false, |guild_member| guild_member.roles.contains(&role));
+
+            let plan = match SubscriptionPlans::<T>::get(guild, role) {
+                Some(plan) if subscription.auto_renew && holds_role => Some(plan),
+                _ => None,
+            };
+
+            if let Some(plan) = plan {
+                let task = ScheduledTask::SubscriptionRenewal(guild, account.clone(), role);
+                let paid_until = subscription.paid_until.saturating_add(plan.period);
+                let next_due = paid_until.max(now.saturating_add(One::one()));
+
+                // The fee is only kept if the next renewal could be scheduled.
+                let renewal = storage::with_transaction(|| {
+                    let renewal = T::Currency::transfer(
+                        &account,
+                        &Self::guild_account(guild),
+                        plan.price,
+                        ExistenceRequirement::KeepAlive,
+                    )
+                    .and_then(|_| Self::schedule_within(next_due, task.clone(), RENEWAL_LOOKAHEAD));
+
+                    match renewal {
+                        Ok(()) => TransactionOutcome::Commit(Ok(())),
+                        Err(error) => TransactionOutcome::Rollback(Err(error)),
+                    }
+                });
+
+                match renewal {
+                    Ok(()) => {
+                        Subscriptions::<T>::insert(
+                            guild,
+                            &key,
+                            Subscription {
+                                paid_until,
+                                auto_renew: true,
+                            },
+                        );
+
+                        Self::deposit_event(Event::SubscriptionRenewed(
+                            guild, account, role, paid_until,
+                        ));
+
+                        return;
+                    }
+                    // Only a failed transfer is a missed payment.
+                    Err(error) if error != DispatchError::from(Error::<T>::SchedulerFull) => {
+                        let grace_until = subscription.paid_until.saturating_add(plan.grace_period);
+                        let retry = now < grace_until
+                            && Self::schedule_within(grace_until, task, RENEWAL_LOOKAHEAD).is_ok();
+
+                        if retry {
+                            Self::deposit_event(Event::SubscriptionPaymentFailed(
+                                guild,
+                                account,
+                                role,
+                                grace_until,
+                            ));
+
+                            return;
+                        }
+                    }
+                    // Not even the wider window had room, so the fee was refunded.
+                    Err(_) => {}
+                }
+            }
+
+            Subscriptions::<T>::remove(guild, &key);
+            GuildMembers::<T>::mutate(guild, &account, |guild_member| {
+                if let Some(guild_member) = guild_member {
+                    guild_member.roles.retain(|held| *held != role);
+                }
+            });
+
+            Self::deposit_event(Event::SubscriptionExpired(guild, account, role));
+        }
+
+        /// Ends the subscription of `member` to `role` as they lose the role, returning whether
+        /// they had one.
+        fn end_subscription(guild: T::DiscordId, member: &T::AccountId, role: RoleIndex) -> bool {
+            let ended = Subscriptions::<T>::take(guild, (member, role)).is_some();
+
+            if ended {
+                Self::deposit_event(Event::SubscriptionExpired(guild, member.clone(), role));
+            }
+
+            ended
+        }
+
+        /// Starts the periodic checks of `member`'s token-gated roles unless they already run.
+        fn enroll_in_token_gate_checks(
+            guild: T::DiscordId,
+            member: &T::AccountId,
+        ) -> DispatchResult {
+            if !TokenGateChecks::<T>::contains_key(guild, member) {
+                Self::schedule_token_gate_check(guild, member.clone())?;
+                TokenGateChecks::<T>::insert(guild, member, ());
+            }
+
+            Ok(())
+        }
+
+        /// Schedules the next periodic check of `member`'s token-gated roles.
+        fn schedule_token_gate_check(guild: T::DiscordId, member: T::AccountId) -> DispatchResult {
+            let period = T::TokenGateCheckPeriod::get().max(One::one());
+            let at = frame_system::Pallet::<T>::block_number().saturating_add(period);
+
+            Self::schedule(at, ScheduledTask::TokenGateCheck(guild, member))
+        }
+
+        /// Grants `member` the token-gated roles of `guild` their free balance qualifies for and
+        /// revokes the others. Roles that would exceed `MaxRolesPerMember` aren't granted.
+        fn apply_token_gates(guild: T::DiscordId, member: &T::AccountId) {
+            let gates = TokenGates::<T>::get(guild);
+
+            if gates.is_empty() {
+                return;
+            }
+
+            let balance = T::Currency::free_balance(member);
+
+            GuildMembers::<T>::mutate(guild, member, |guild_member| {
+                let guild_member = match guild_member {
+                    Some(guild_member) => guild_member,
+                    None => return,
+                };
+
+                for (role, min_balance) in gates.iter() {
+                    let held = guild_member.roles.contains(role);
+
+                    if balance >= *min_balance {
+                        if !held && guild_member.roles.try_push(*role).is_ok() {
+                            Self::deposit_event(Event::TokenGatedRoleGranted(
+                                guild,
+                                member.clone(),
+                                *role,
+                            ));
+                        }
+                    } else if held {
+                        guild_member.roles.retain(|held| held != role);
+                        Self::end_subscription(guild, member, *role);
+
+                        Self::deposit_event(Event::TokenGatedRoleRevoked(
+                            guild,
+                            member.clone(),
+                            *role,
+                        ));
+                    }
+                }
+            });
+        }
+
+        /// Ensures a member moderating `target` holds a role strictly above the target's highest
+        /// role. As with roles, bots aren't restricted.
+        fn ensure_above_member(
+            guild: T::DiscordId,
+            actor: &Actor<T::AccountId>,
+            target: &T::AccountId,
+        ) -> DispatchResult {
+            if let Actor::Member(who) = actor {
+                ensure!(
+                    Self::highest_role_position(guild, who)
+                        > Self::highest_role_position(guild, target),
+                    Error::<T>::MemberAboveActor
+                );
+            }
+
+            Ok(())
+        }
+
+        /// Looks up a role by its on-chain index or its Discord id.
+        fn resolve_role(
+            guild: T::DiscordId,
+            role: RoleRef<T::DiscordId>,
+        ) -> Result<(RoleIndex, RoleOf<T>), DispatchError> {
+            let index = match role {
+                RoleRef::Index(index) => index,
+                RoleRef::Id(id) => {
+                    RoleIdToIndex::<T>::get(guild, id).ok_or(Error::<T>::RoleDoesntExist)?
+                }
+            };
+
+            let role = Roles::<T>::get(guild, index).ok_or(Error::<T>::RoleDoesntExist)?;
+
+            Ok((index, role))
+        }
+
+        /// Whether `member` holds the role of `guild` at `index`.
+        pub fn has_role(guild: T::DiscordId, member: &T::AccountId, index: RoleIndex) -> bool {
+            GuildMembers::<T>::get(guild, member)
+                .map_or(false, |guild_member| guild_member.roles.contains(&index))
+        }
+
+        /// The guild's @everyone role, which shares its Discord id with the guild.
+        pub fn everyone_role(guild: T::DiscordId) -> Option<RoleIndex> {
+            RoleIdToIndex::<T>::get(guild, guild)
+        }
+
+        /// Removes every reference to a deleted role from the guild's members, banned members,
+        /// channel overwrites, token gates, subscription plans and treasury. Fails without
+        /// touching storage if the guild holds more members, banned members and channels than
+        /// `witness`, which the call's weight was charged for.
+        fn remove_role_references(
+            guild: T::DiscordId,
+            role: RoleIndex,
+            witness: u32,
+        ) -> DispatchResult {
+            // Each scan stops one entry past the witness, so an understated witness is caught
+            // before reading more than it paid for.
+            let limit = (witness as usize).saturating_add(1);
+            let mut scanned: usize = 0;
+
+            let holders: Vec<T::AccountId> = GuildMembers::<T>::iter_prefix(guild)
+                .take(limit)
+                .inspect(|_| scanned += 1)
+                .filter(|(_, guild_member)| guild_member.roles.contains(&role))
+                .map(|(account, _)| account)
+                .collect();
+            ensure!(scanned < limit, Error::<T>::InvalidWitness);
+
+            let banned: Vec<T::AccountId> = BannedMembers::<T>::iter_prefix(guild)
+                .take(limit - scanned)
+                .inspect(|_| scanned += 1)
+                .filter(|(_, (guild_member, _, _))| guild_member.roles.contains(&role))
+                .map(|(account, _)| account)
+                .collect();
+            ensure!(scanned < limit, Error::<T>::InvalidWitness);
+
+            let target = OverwriteTarget::Role(role);
+            let channels: Vec<T::DiscordId> = Channels::<T>::iter_prefix(guild)
+                .take(limit - scanned)
+                .inspect(|_| scanned += 1)
+                .filter(|(_, channel)| {
+                    channel
+                        .permission_overwrites
+                        .iter()
+                        .any(|overwrite| overwrite.target == target)
+                })
+                .map(|(channel_id, _)| channel_id)
+                .collect();
+            ensure!(scanned < limit, Error::<T>::InvalidWitness);
+
+            for account in holders {
+                GuildMembers::<T>::mutate(guild, &account, |guild_member| {
+                    if let Some(guild_member) = guild_member {
+                        guild_member.roles.retain(|held| *held != role);
+                    }
+                });
+
+                Self::end_subscription(guild, &account, role);
+            }
+
+            for account in banned {
+                BannedMembers::<T>::mutate(guild, account, |ban| {
+                    if let Some((guild_member, _, _)) = ban {
+                        guild_member.roles.retain(|held| *held != role);
+                    }
+                });
+            }
+
+            for channel_id in channels {
+                Channels::<T>::mutate(guild, channel_id, |channel| {
+                    if let Some(channel) = channel {
+                        channel
+                            .permission_overwrites
+                            .retain(|overwrite| overwrite.target != target);
+                    }
+                });
+            }
+
+            TokenGates::<T>::mutate(guild, |gates| gates.retain(|(gated, _)| *gated != role));
+            SubscriptionPlans::<T>::remove(guild, role);
+
+            if TreasuryRole::<T>::get(guild) == Some(role) {
+                TreasuryRole::<T>::remove(guild);
+            }
+
+            Ok(())
+        }
+
+        /// Computes the permissions `member` effectively has in `guild`, optionally inside
+        /// `channel`, following Discord's algorithm: the @everyone role and the member's roles
+        /// are combined, `ADMINISTRATOR` grants everything, channel overwrites are applied in the
+        /// order @everyone, roles, member, and timed out members are restricted to reading.
+        pub fn effective_permissions(
+            guild: T::DiscordId,
+            member: &T::AccountId,
+            channel: Option<T::DiscordId>,
+        ) -> PermissionSet {
+            let guild_member = match GuildMembers::<T>::get(guild, member) {
+                Some(guild_member) => guild_member,
+                None => return PermissionSet::empty(),
+            };
+
+            let everyone = Self::everyone_role(guild);
+
+            let mut permissions = everyone
+                .and_then(|everyone| Roles::<T>::get(guild, everyone))
+                .map(|role| role.permissions)
+                .unwrap_or_default();
+
+            for role in guild_member
+                .roles
+                .iter()
+                .filter_map(|role| Roles::<T>::get(guild, role))
+            {
+                permissions |= role.permissions;
+            }
+
+            if permissions.contains(PermissionSet::ADMINISTRATOR) {
+                return PermissionSet::all();
+            }
+
+            if let Some(channel) = channel.and_then(|channel| Channels::<T>::get(guild, channel)) {
+                let overwrites = channel.permission_overwrites;
+
+                if let Some(everyone_overwrite) = everyone.and_then(|everyone| {
+                    overwrites
+                        .iter()
+                        .find(|overwrite| overwrite.target == OverwriteTarget::Role(everyone))
+                }) {
+                    permissions.remove(everyone_overwrite.deny);
+                    permissions.insert(everyone_overwrite.allow);
+                }
+
+                let mut allow = PermissionSet::empty();
+                let mut deny = PermissionSet::empty();
+
+                for overwrite in overwrites.iter() {
+                    if let OverwriteTarget::Role(role) = &overwrite.target {
+                        if guild_member.roles.contains(role) {
+                            allow |= overwrite.allow;
+                            deny |= overwrite.deny;
+                        }
+                    }
+                }
+
+                permissions.remove(deny);
+                permissions.insert(allow);
+
+                if let Some(member_overwrite) = overwrites
+                    .iter()
+                    .find(|overwrite| overwrite.target == OverwriteTarget::Member(member.clone()))
+                {
+                    permissions.remove(member_overwrite.deny);
+                    permissions.insert(member_overwrite.allow);
+                }
+            }
+
+            // Timed out members can only read, like on Discord.
+            if guild_member
+                .communication_disabled_until
+                .map_or(false, |until| {
+                    until > frame_system::Pallet::<T>::block_number()
+                })
+            {
+                permissions &= PermissionSet::VIEW_CHANNEL | PermissionSet::READ_MESSAGE_HISTORY;
+            }
+
+            permissions
+        }
+
+        /// Whether `member` effectively holds `permission` in `guild`, optionally inside `channel`.
+        pub fn has_permission(
+            guild: T::DiscordId,
+            member: &T::AccountId,
+            channel: Option<T::DiscordId>,
+            permission: Permissions,
+        ) -> bool {
+            Self::effective_permissions(guild, member, channel).contains(permission.into())
+        }
     }
 }
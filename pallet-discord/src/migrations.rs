@@ -0,0 +1,352 @@
+//! Storage migrations for pallet-discord.
+
+use super::*;
+use crate::primitives::*;
+#[cfg(feature = "try-runtime")]
+use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+use frame_support::{
+    pallet_prelude::*,
+    storage::{self, migration},
+    traits::{OnRuntimeUpgrade, PalletInfoAccess, StorageVersion},
+};
+use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData, vec::Vec};
+
+const LOG_TARGET: &str = "runtime::discord";
+
+/// Storage layout of the unversioned pallet, which tracked a single guild and keyed roles by
+/// name.
+pub mod v0 {
+    use super::*;
+
+    #[derive(Encode, Decode)]
+    pub struct GuildMember<AccountId> {
+        pub account: AccountId,
+        pub roles: Vec<Vec<u8>>,
+        pub deaf: bool,
+        pub mute: bool,
+    }
+
+    #[derive(Encode, Decode)]
+    pub struct Role<RoleId> {
+        pub id: Option<RoleId>,
+        pub name: Vec<u8>,
+        pub color: u64,
+        pub hoist: bool,
+        pub position: u64,
+        pub permissions: Vec<Permissions>,
+        pub managed: bool,
+        pub mentionable: bool,
+    }
+
+    /// A ban represented as (member, ban reason).
+    pub type Ban<AccountId> = (GuildMember<AccountId>, Vec<u8>);
+}
+
+/// Moves the unversioned storage under `LegacyGuild` and converts it to storage version 1:
+/// members, bans, roles and bots are keyed by guild, roles get an index, and values are bounded.
+///
+/// Roles whose name is longer than `MaxNameLength` are dropped, as are references to them, ban
+/// reasons are truncated to `MaxReasonLength` and members keep at most `MaxRolesPerMember` roles.
+pub struct MigrateToV1<T, LegacyGuild>(PhantomData<(T, LegacyGuild)>);
+
+impl<T: Config, LegacyGuild: Get<T::DiscordId>> OnRuntimeUpgrade for MigrateToV1<T, LegacyGuild> {
+    fn on_runtime_upgrade() -> Weight {
+        if StorageVersion::get::<Pallet<T>>() != 0 {
+            log::info!(
+                target: LOG_TARGET,
+                "skipping v1 migration, storage is already past v0"
+            );
+            return T::DbWeight::get().reads(1);
+        }
+
+        let pallet = Pallet::<T>::name().as_bytes();
+        let guild = LegacyGuild::get();
+
+        // Old and new entries share their storage prefixes, so every old entry is drained before
+        // any new one is written.
+        let roles: Vec<(Vec<u8>, v0::Role<T::DiscordId>)> =
+            migration::storage_key_iter::<Vec<u8>, _, Blake2_128Concat>(pallet, b"Roles")
+                .drain()
+                .collect();
+        let members: Vec<(T::AccountId, v0::GuildMember<T::AccountId>)> =
+            migration::storage_key_iter::<T::AccountId, _, Blake2_128Concat>(
+                pallet,
+                b"GuildMembers",
+            )
+            .drain()
+            .collect();
+        let bans: Vec<(T::AccountId, v0::Ban<T::AccountId>)> = migration::storage_key_iter::<
+            T::AccountId,
+            _,
+            Blake2_128Concat,
+        >(pallet, b"BannedMembers")
+        .drain()
+        .collect();
+        let bots: Vec<(T::AccountId, ())> =
+            migration::storage_key_iter::<T::AccountId, _, Blake2_128Concat>(pallet, b"Bots")
+                .drain()
+                .collect();
+
+        let migrated = (roles.len() + members.len() + bans.len() + bots.len()) as Weight;
+
+        if !Guilds::<T>::contains_key(guild) {
+            Guilds::<T>::insert(
+                guild,
+                Guild {
+                    id: guild,
+                    name: Default::default(),
+                    icon: Default::default(),
+                    owner_id: Default::default(),
+                    afk_channel_id: Default::default(),
+                    afk_timeout: Default::default(),
+                    verification_level: Default::default(),
+                    default_message_notifications: Default::default(),
+                    explicit_content_filter: Default::default(),
+                    rules_channel_id: Default::default(),
+                    system_channel_id: Default::default(),
+                },
+            );
+        }
+
+        let mut role_indices = BTreeMap::new();
+        let mut next_index: RoleIndex = 0;
+
+        for (name, role) in roles {
+            let bounded_name: NameOf<T> = match name.clone().try_into() {
+                Ok(bounded_name) => bounded_name,
+                Err(_) => {
+                    log::warn!(
+                        target: LOG_TARGET,
+                        "dropping role with a name longer than MaxNameLength"
+                    );
+                    continue;
+                }
+            };
+
+            let index = next_index;
+            next_index += 1;
+
+            if let Some(id) = role.id {
+                RoleIdToIndex::<T>::insert(guild, id, index);
+            }
+
+            Roles::<T>::insert(
+                guild,
+                index,
+                Role {
+                    id: role.id,
+                    name: bounded_name,
+                    color: role.color,
+                    hoist: role.hoist,
+                    position: role.position,
+                    permissions: role.permissions.into(),
+                    managed: role.managed,
+                    mentionable: role.mentionable,
+                },
+            );
+
+            role_indices.insert(name, index);
+        }
+
+        NextRoleIndex::<T>::insert(guild, next_index);
+
+        let migrate_member = |member: v0::GuildMember<T::AccountId>| -> GuildMemberOf<T> {
+            let mut roles = BoundedVec::default();
+
+            for index in member
+                .roles
+                .iter()
+                .filter_map(|name| role_indices.get(name))
+            {
+                if roles.try_push(*index).is_err() {
+                    log::warn!(
+                        target: LOG_TARGET,
+                        "member holds more than MaxRolesPerMember roles, dropping the rest"
+                    );
+                    break;
+                }
+            }
+
+            GuildMember {
+                account: member.account,
+                roles,
+                deaf: member.deaf,
+                mute: member.mute,
+                communication_disabled_until: None,
+            }
+        };
+
+        for (account, member) in members {
+            GuildMembers::<T>::insert(guild, account, migrate_member(member));
+        }
+
+        for (account, (member, mut reason)) in bans {
+            reason.truncate(T::MaxReasonLength::get() as usize);
+
+            BannedMembers::<T>::insert(
+                guild,
+                account,
+                (
+                    migrate_member(member),
+                    ReasonOf::<T>::try_from(reason).unwrap_or_default(),
+                    None,
+                ),
+            );
+        }
+
+        // v1 bots carry no metadata, their records are filled in by `MigrateToV2`.
+        for (account, ()) in bots {
+            storage::unhashed::put(&Bots::<T>::hashed_key_for(guild, account), &());
+        }
+
+        StorageVersion::new(1).put::<Pallet<T>>();
+
+        log::info!(
+            target: LOG_TARGET,
+            "migrated {} entries to v1",
+            migrated
+        );
+
+        T::DbWeight::get().reads_writes(migrated + 2, migrated * 2 + 4)
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade() -> Result<(), &'static str> {
+        if StorageVersion::get::<Pallet<T>>() != 0 {
+            return Ok(());
+        }
+
+        let pallet = Pallet::<T>::name().as_bytes();
+
+        let members = migration::storage_key_iter::<
+            T::AccountId,
+            v0::GuildMember<T::AccountId>,
+            Blake2_128Concat,
+        >(pallet, b"GuildMembers")
+        .count() as u32;
+        let bans = migration::storage_key_iter::<
+            T::AccountId,
+            v0::Ban<T::AccountId>,
+            Blake2_128Concat,
+        >(pallet, b"BannedMembers")
+        .count() as u32;
+        let bots =
+            migration::storage_key_iter::<T::AccountId, (), Blake2_128Concat>(pallet, b"Bots")
+                .count() as u32;
+
+        Self::set_temp_storage((members, bans, bots), "v0_counts");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade() -> Result<(), &'static str> {
+        // Later migrations may already have run on top of v1.
+        ensure!(
+            StorageVersion::get::<Pallet<T>>() >= 1,
+            "storage was not migrated to v1"
+        );
+
+        let (members, bans, bots): (u32, u32, u32) = match Self::get_temp_storage("v0_counts") {
+            Some(counts) => counts,
+            None => return Ok(()),
+        };
+
+        let guild = LegacyGuild::get();
+
+        ensure!(
+            Guilds::<T>::contains_key(guild),
+            "migrated guild isn't registered"
+        );
+        ensure!(
+            GuildMembers::<T>::iter_prefix(guild).count() as u32 == members,
+            "members were lost in the v1 migration"
+        );
+        ensure!(
+            BannedMembers::<T>::iter_prefix(guild).count() as u32 == bans,
+            "bans were lost in the v1 migration"
+        );
+        ensure!(
+            Bots::<T>::iter_key_prefix(guild).count() as u32 == bots,
+            "bots were lost in the v1 migration"
+        );
+        ensure!(
+            Roles::<T>::iter_prefix(guild).count() as u32 == NextRoleIndex::<T>::get(guild),
+            "role indices don't match the migrated roles"
+        );
+
+        Ok(())
+    }
+}
+
+/// Converts storage version 1 to 2: bots get a record holding their metadata.
+///
+/// Bots registered before v2 keep the access they had, every capability, under an empty name
+/// and a default application id, until they are registered again.
+pub struct MigrateToV2<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+    fn on_runtime_upgrade() -> Weight {
+        if StorageVersion::get::<Pallet<T>>() != 1 {
+            log::info!(
+                target: LOG_TARGET,
+                "skipping v2 migration, storage isn't at v1"
+            );
+            return T::DbWeight::get().reads(1);
+        }
+
+        let mut migrated: Weight = 0;
+
+        Bots::<T>::translate_values::<(), _>(|()| {
+            migrated += 1;
+
+            Some(BotInfo {
+                name: Default::default(),
+                application_id: Default::default(),
+                capabilities: BotCapabilities::all(),
+            })
+        });
+
+        StorageVersion::new(2).put::<Pallet<T>>();
+
+        log::info!(
+            target: LOG_TARGET,
+            "migrated {} bots to v2",
+            migrated
+        );
+
+        T::DbWeight::get().reads_writes(migrated + 1, migrated + 1)
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade() -> Result<(), &'static str> {
+        // Coming from v0, the bots are counted by `MigrateToV1` instead.
+        if StorageVersion::get::<Pallet<T>>() != 1 {
+            return Ok(());
+        }
+
+        Self::set_temp_storage(Bots::<T>::iter_keys().count() as u32, "v1_bots");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade() -> Result<(), &'static str> {
+        ensure!(
+            StorageVersion::get::<Pallet<T>>() == 2,
+            "storage was not migrated to v2"
+        );
+
+        let bots: u32 = match Self::get_temp_storage("v1_bots") {
+            Some(bots) => bots,
+            None => return Ok(()),
+        };
+
+        ensure!(
+            Bots::<T>::iter().count() as u32 == bots,
+            "bots were lost in the v2 migration"
+        );
+
+        Ok(())
+    }
+}
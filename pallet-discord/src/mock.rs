@@ -0,0 +1,167 @@
+use crate as pallet_discord;
+use frame_support::{parameter_types, traits::GenesisBuild, PalletId};
+use frame_system as system;
+use sp_core::H256;
+use sp_keystore::{testing::KeyStore, KeystoreExt};
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentifyAccount, IdentityLookup, Verify},
+    MultiSignature,
+};
+use std::sync::Arc;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub type Signature = MultiSignature;
+pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+        Discord: pallet_discord::{Pallet, Call, Config<T>, Storage, Event<T>},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = frame_support::traits::Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ();
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+    type Balance = u64;
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const MaxScheduledPerBlock: u32 = 2;
+    pub const MaxNameLength: u32 = 16;
+    pub const MaxReasonLength: u32 = 32;
+    pub const MaxRolesPerMember: u32 = 4;
+    pub const MaxOverwritesPerChannel: u32 = 4;
+    pub const MaxBotThreshold: u32 = 3;
+    pub const BotProposalLifetime: u64 = 5;
+    pub const MaxOpenBotProposals: u32 = 2;
+    pub const MaxTokenGates: u32 = 2;
+    pub const TokenGateCheckPeriod: u64 = 10;
+    pub const DiscordPalletId: PalletId = PalletId(*b"py/dscrd");
+    pub const RoleDeposit: u64 = 5;
+    pub const MemberDeposit: u64 = 3;
+    pub const BanDepositPerByte: u64 = 1;
+    pub const MaxSpendApprovals: u32 = 3;
+    pub const MaxSpendHistory: u32 = 2;
+}
+
+impl pallet_discord::Config for Test {
+    type Event = Event;
+    type DiscordId = u64;
+    type Signature = Signature;
+    type Signer = <Signature as Verify>::Signer;
+    type MaxScheduledPerBlock = MaxScheduledPerBlock;
+    type MaxNameLength = MaxNameLength;
+    type MaxReasonLength = MaxReasonLength;
+    type MaxRolesPerMember = MaxRolesPerMember;
+    type MaxOverwritesPerChannel = MaxOverwritesPerChannel;
+    type MaxBotThreshold = MaxBotThreshold;
+    type BotProposalLifetime = BotProposalLifetime;
+    type MaxOpenBotProposals = MaxOpenBotProposals;
+    type Currency = Balances;
+    type RoleDeposit = RoleDeposit;
+    type MemberDeposit = MemberDeposit;
+    type BanDepositPerByte = BanDepositPerByte;
+    type MaxTokenGates = MaxTokenGates;
+    type TokenGateCheckPeriod = TokenGateCheckPeriod;
+    type PalletId = DiscordPalletId;
+    type MaxSpendApprovals = MaxSpendApprovals;
+    type MaxSpendHistory = MaxSpendHistory;
+    type WeightInfo = ();
+}
+
+/// The guild registered by `tests::setup_guild`, which is also the id of its @everyone role.
+pub const GUILD: u64 = 1;
+
+pub fn account(seed: u8) -> AccountId {
+    AccountId::from([seed; 32])
+}
+
+pub fn bot() -> AccountId {
+    account(1)
+}
+
+pub fn alice() -> AccountId {
+    account(2)
+}
+
+pub fn bob() -> AccountId {
+    account(3)
+}
+
+pub fn charlie() -> AccountId {
+    account(4)
+}
+
+/// Balance the test accounts start with, enough for the deposits of everything they write.
+pub const INITIAL_BALANCE: u64 = 1_000;
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![bot(), alice(), bob(), charlie()]
+            .into_iter()
+            .map(|account| (account, INITIAL_BALANCE))
+            .collect(),
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    let mut ext = sp_io::TestExternalities::new(storage);
+    ext.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
@@ -0,0 +1,165 @@
+//! Origins gating calls of other pallets on the roles and permissions of guild members.
+//!
+//! For instance, a runtime can let members holding `ADMINISTRATOR` in its main guild authorize
+//! upgrades:
+//!
+//! ```ignore
+//! parameter_types! {
+//!     pub const SocietyGuild: u64 = 1;
+//!     pub const Administrator: PermissionSet = PermissionSet::ADMINISTRATOR;
+//! }
+//!
+//! type EnsureAdministrator = EnsurePermission<Runtime, SocietyGuild, Administrator>;
+//! ```
+
+use super::*;
+use crate::primitives::*;
+use frame_support::traits::{EnsureOrigin, Get};
+use frame_system::RawOrigin;
+use sp_std::marker::PhantomData;
+
+/// Ensures the origin is signed by a member of `Guild` holding the role with the Discord id
+/// `RoleId`. Discord never reuses ids, so renaming roles or creating new ones can't hand the
+/// origin to other members. Succeeds with the member's account.
+pub struct EnsureRole<T, Guild, RoleId>(PhantomData<(T, Guild, RoleId)>);
+
+impl<O, T, Guild, RoleId> EnsureOrigin<O> for EnsureRole<T, Guild, RoleId>
+where
+    O: Into<Result<RawOrigin<T::AccountId>, O>> + From<RawOrigin<T::AccountId>>,
+    T: Config,
+    Guild: Get<T::DiscordId>,
+    RoleId: Get<T::DiscordId>,
+{
+    type Success = T::AccountId;
+
+    fn try_origin(o: O) -> Result<Self::Success, O> {
+        let guild = Guild::get();
+
+        o.into().and_then(|o| match o {
+            RawOrigin::Signed(who)
+                if RoleIdToIndex::<T>::get(guild, RoleId::get())
+                    .map_or(false, |index| Pallet::<T>::has_role(guild, &who, index)) =>
+            {
+                Ok(who)
+            }
+            r => Err(O::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> O {
+        let guild = Guild::get();
+        let role = benchmarking::role::<T>(guild, Some(RoleId::get()), PermissionSet::empty());
+
+        benchmarking::member_with_role::<T, O>(guild, role)
+    }
+}
+
+/// Ensures the origin is signed by a member of `Guild` whose guild-wide permissions include all
+/// of `Required`. Succeeds with the member's account.
+pub struct EnsurePermission<T, Guild, Required>(PhantomData<(T, Guild, Required)>);
+
+impl<O, T, Guild, Required> EnsureOrigin<O> for EnsurePermission<T, Guild, Required>
+where
+    O: Into<Result<RawOrigin<T::AccountId>, O>> + From<RawOrigin<T::AccountId>>,
+    T: Config,
+    Guild: Get<T::DiscordId>,
+    Required: Get<PermissionSet>,
+{
+    type Success = T::AccountId;
+
+    fn try_origin(o: O) -> Result<Self::Success, O> {
+        o.into().and_then(|o| match o {
+            RawOrigin::Signed(who)
+                if Pallet::<T>::effective_permissions(Guild::get(), &who, None)
+                    .contains(Required::get()) =>
+            {
+                Ok(who)
+            }
+            r => Err(O::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> O {
+        let guild = Guild::get();
+        let role = benchmarking::role::<T>(guild, None, Required::get());
+
+        benchmarking::member_with_role::<T, O>(guild, role)
+    }
+}
+
+/// Sets up the storage the successful origins rely on, as the pallets benchmarking against them
+/// know nothing about guilds.
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking {
+    use super::*;
+    use frame_support::BoundedVec;
+
+    /// The role of `guild` with the Discord id `id`, created if needed or if `id` is `None`,
+    /// granting at least `permissions`.
+    pub fn role<T: Config>(
+        guild: T::DiscordId,
+        id: Option<T::DiscordId>,
+        permissions: PermissionSet,
+    ) -> RoleIndex {
+        if let Some(index) = id.and_then(|id| RoleIdToIndex::<T>::get(guild, id)) {
+            Roles::<T>::mutate(guild, index, |role| {
+                if let Some(role) = role {
+                    role.permissions |= permissions;
+                }
+            });
+
+            return index;
+        }
+
+        let index = NextRoleIndex::<T>::get(guild);
+        NextRoleIndex::<T>::insert(guild, index + 1);
+        if let Some(id) = id {
+            RoleIdToIndex::<T>::insert(guild, id, index);
+        }
+        Roles::<T>::insert(
+            guild,
+            index,
+            Role {
+                id,
+                name: Default::default(),
+                color: 0,
+                hoist: false,
+                position: 0,
+                permissions,
+                managed: false,
+                mentionable: false,
+            },
+        );
+
+        index
+    }
+
+    /// Gives `role` to the whitelisted caller, returning it as a signed origin.
+    pub fn member_with_role<T: Config, O: From<RawOrigin<T::AccountId>>>(
+        guild: T::DiscordId,
+        role: RoleIndex,
+    ) -> O {
+        let who: T::AccountId = frame_benchmarking::whitelisted_caller();
+
+        GuildMembers::<T>::mutate(guild, &who, |guild_member| {
+            let guild_member = guild_member.get_or_insert_with(|| GuildMember {
+                account: who.clone(),
+                roles: BoundedVec::default(),
+                deaf: false,
+                mute: false,
+                communication_disabled_until: None,
+            });
+
+            if !guild_member.roles.contains(&role) {
+                guild_member
+                    .roles
+                    .try_push(role)
+                    .expect("benchmarks give the caller few roles; qed");
+            }
+        });
+
+        O::from(RawOrigin::Signed(who))
+    }
+}
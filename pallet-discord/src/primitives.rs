@@ -1,34 +1,62 @@
 #![allow(non_camel_case_types, clippy::upper_case_acronyms)]
 
-use codec::{Decode, Encode};
+use codec::{Decode, Encode, MaxEncodedLen};
 use scale_info::TypeInfo;
 use sp_std::vec::Vec;
 
-#[derive(Encode, Decode, TypeInfo, Clone)]
-pub struct GuildMember<AccountId> {
+/// On-chain index of a role inside its guild, stable across renames.
+pub type RoleIndex = u32;
+
+/// Index of a spend proposal inside its guild.
+pub type SpendIndex = u32;
+
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone)]
+pub struct GuildMember<AccountId, BlockNumber, Roles> {
     pub account: AccountId,
-    pub roles: Vec<Vec<u8>>,
+    pub roles: Roles,
     pub deaf: bool,
     pub mute: bool,
+    /// Block until which the member is timed out.
+    pub communication_disabled_until: Option<BlockNumber>,
 }
 
-#[derive(Encode, Decode, TypeInfo)]
-pub struct Role<RoleId> {
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub struct Role<RoleId, Name> {
     pub id: Option<RoleId>,
-    pub name: Vec<u8>,
+    pub name: Name,
     pub color: u64,
     pub hoist: bool,
     pub position: u64,
-    pub permissions: Vec<Permissions>,
+    pub permissions: PermissionSet,
     pub managed: bool,
     pub mentionable: bool,
 }
 
-#[derive(Encode, Decode, TypeInfo)]
-pub struct Guild<DiscordId> {
+/// A role referenced either by its on-chain index or by its Discord id.
+#[derive(Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RoleRef<DiscordId> {
+    Index(RoleIndex),
+    Id(DiscordId),
+}
+
+/// Work the pallet runs at the start of a future block.
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
+pub enum ScheduledTask<AccountId, DiscordId> {
+    /// Lifts the ban of (guild, member) if it's still due to expire.
+    BanExpiry(DiscordId, AccountId),
+    /// Lifts the timeout of (guild, member) if it's still due to expire.
+    TimeoutExpiry(DiscordId, AccountId),
+    /// Re-evaluates the token-gated roles of (guild, member).
+    TokenGateCheck(DiscordId, AccountId),
+    /// Charges the subscription of (guild, member) to a role, or revokes it, once it's due.
+    SubscriptionRenewal(DiscordId, AccountId, RoleIndex),
+}
+
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
+pub struct Guild<DiscordId, Name> {
     pub id: DiscordId,
-    pub name: Vec<u8>,
-    pub icon: Vec<u8>,
+    pub name: Name,
+    pub icon: Name,
     pub owner_id: DiscordId,
     pub afk_channel_id: DiscordId,
     pub afk_timeout: u64,
@@ -39,15 +67,98 @@ pub struct Guild<DiscordId> {
     pub system_channel_id: DiscordId,
 }
 
-#[derive(Encode, Decode, TypeInfo)]
-pub struct Channel<AccountId, DiscordId> {
+/// An item of a guild a deposit is reserved for.
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
+pub enum DepositKey<AccountId> {
+    Role(RoleIndex),
+    Member(AccountId),
+    /// The reason of a ban.
+    Ban(AccountId),
+}
+
+/// Recurring fee members pay to hold a role.
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
+pub struct SubscriptionPlan<Balance, BlockNumber> {
+    /// Charged at the start of every period.
+    pub price: Balance,
+    /// Length of a billing period in blocks.
+    pub period: BlockNumber,
+    /// Blocks a subscriber keeps the role after failing to pay before it's revoked.
+    pub grace_period: BlockNumber,
+}
+
+/// A member's subscription to a role.
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
+pub struct Subscription<BlockNumber> {
+    /// Block the paid period ends at, when the next fee is due.
+    pub paid_until: BlockNumber,
+    /// Whether the subscription is renewed at the end of the paid period.
+    pub auto_renew: bool,
+}
+
+/// An administrative action submitted by bots, waiting for the guild's bot threshold.
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
+pub struct BotProposal<BlockNumber, Approvals> {
+    /// Bots that submitted the action so far.
+    pub approvals: Approvals,
+    /// Block the approvals are dropped at unless the threshold is reached first.
+    pub expires_at: BlockNumber,
+}
+
+/// A proposal to pay out of a guild's treasury, waiting for approvals.
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
+pub struct SpendProposal<AccountId, Balance, Description, Approvals> {
+    pub proposer: AccountId,
+    pub beneficiary: AccountId,
+    pub amount: Balance,
+    pub description: Description,
+    /// Members who approved the spend, the proposer included.
+    pub approvals: Approvals,
+}
+
+/// A spend paid out of a guild's treasury.
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
+pub struct Spend<AccountId, Balance, BlockNumber> {
+    pub index: SpendIndex,
+    pub beneficiary: AccountId,
+    pub amount: Balance,
+    pub paid_at: BlockNumber,
+}
+
+/// A bot account authorized for a guild.
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
+pub struct BotInfo<DiscordId, Name> {
+    pub name: Name,
+    /// Id of the bot's Discord application.
+    pub application_id: DiscordId,
+    /// What the bot is allowed to do in the guild.
+    pub capabilities: BotCapabilities,
+}
+
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
+pub struct Channel<DiscordId, Overwrites> {
     pub id: DiscordId,
-    pub channe_type: ChannelType,
+    pub channel_type: ChannelType,
     pub position: u64,
-    pub permission_overwrites: Vec<(AccountId, Vec<Permissions>)>,
+    pub permission_overwrites: Overwrites,
+}
+
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
+pub struct PermissionOverwrite<AccountId> {
+    pub target: OverwriteTarget<AccountId>,
+    pub allow: PermissionSet,
+    pub deny: PermissionSet,
 }
 
-#[derive(Encode, Decode, TypeInfo)]
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
+pub enum OverwriteTarget<AccountId> {
+    /// Applies to every member holding this role.
+    Role(RoleIndex),
+    /// Applies to a single member.
+    Member(AccountId),
+}
+
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum ChannelType {
     GUILD_TEXT,
     GUILD_VOICE,
@@ -59,7 +170,7 @@ pub enum ChannelType {
     GUILD_STAGE_VOICE,
 }
 
-#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Permissions {
     CREATE_INSTANT_INVITE,
     KICK_MEMBERS,
@@ -103,3 +214,169 @@ pub enum Permissions {
     START_EMBEDDED_ACTIVITIES,
     MODERATE_MEMBERS,
 }
+
+impl Permissions {
+    pub const ALL: [Permissions; 41] = [
+        Permissions::CREATE_INSTANT_INVITE,
+        Permissions::KICK_MEMBERS,
+        Permissions::BAN_MEMBERS,
+        Permissions::ADMINISTRATOR,
+        Permissions::MANAGE_CHANNELS,
+        Permissions::MANAGE_GUILD,
+        Permissions::ADD_REACTIONS,
+        Permissions::VIEW_AUDIT_LOG,
+        Permissions::PRIORITY_SPEAKER,
+        Permissions::STREAM,
+        Permissions::VIEW_CHANNEL,
+        Permissions::SEND_MESSAGES,
+        Permissions::SEND_TTS_MESSAGES,
+        Permissions::MANAGE_MESSAGES,
+        Permissions::EMBED_LINKS,
+        Permissions::ATTACH_FILES,
+        Permissions::READ_MESSAGE_HISTORY,
+        Permissions::MENTION_EVERYONE,
+        Permissions::USE_EXTERNAL_EMOJIS,
+        Permissions::VIEW_GUILD_INSIGHTS,
+        Permissions::CONNECT,
+        Permissions::SPEAK,
+        Permissions::MUTE_MEMBERS,
+        Permissions::DEAFEN_MEMBERS,
+        Permissions::MOVE_MEMBERS,
+        Permissions::USE_VAD,
+        Permissions::CHANGE_NICKNAME,
+        Permissions::MANAGE_NICKNAMES,
+        Permissions::MANAGE_ROLES,
+        Permissions::MANAGE_WEBHOOKS,
+        Permissions::MANAGE_EMOJIS_AND_STICKERS,
+        Permissions::USE_APPLICATION_COMMANDS,
+        Permissions::REQUEST_TO_SPEAK,
+        Permissions::MANAGE_EVENTS,
+        Permissions::MANAGE_THREADS,
+        Permissions::CREATE_PUBLIC_THREADS,
+        Permissions::CREATE_PRIVATE_THREADS,
+        Permissions::USE_EXTERNAL_STICKERS,
+        Permissions::SEND_MESSAGES_IN_THREADS,
+        Permissions::START_EMBEDDED_ACTIVITIES,
+        Permissions::MODERATE_MEMBERS,
+    ];
+}
+
+bitflags::bitflags! {
+    /// Discord's 64-bit permission bitfield.
+    ///
+    /// Encodes exactly like the raw `u64` Discord uses, and bits that don't have a named flag
+    /// yet are kept as-is, so a value coming from Discord round-trips unchanged.
+    #[derive(Encode, Decode, TypeInfo, MaxEncodedLen)]
+    pub struct PermissionSet: u64 {
+        const CREATE_INSTANT_INVITE = 1 << 0;
+        const KICK_MEMBERS = 1 << 1;
+        const BAN_MEMBERS = 1 << 2;
+        const ADMINISTRATOR = 1 << 3;
+        const MANAGE_CHANNELS = 1 << 4;
+        const MANAGE_GUILD = 1 << 5;
+        const ADD_REACTIONS = 1 << 6;
+        const VIEW_AUDIT_LOG = 1 << 7;
+        const PRIORITY_SPEAKER = 1 << 8;
+        const STREAM = 1 << 9;
+        const VIEW_CHANNEL = 1 << 10;
+        const SEND_MESSAGES = 1 << 11;
+        const SEND_TTS_MESSAGES = 1 << 12;
+        const MANAGE_MESSAGES = 1 << 13;
+        const EMBED_LINKS = 1 << 14;
+        const ATTACH_FILES = 1 << 15;
+        const READ_MESSAGE_HISTORY = 1 << 16;
+        const MENTION_EVERYONE = 1 << 17;
+        const USE_EXTERNAL_EMOJIS = 1 << 18;
+        const VIEW_GUILD_INSIGHTS = 1 << 19;
+        const CONNECT = 1 << 20;
+        const SPEAK = 1 << 21;
+        const MUTE_MEMBERS = 1 << 22;
+        const DEAFEN_MEMBERS = 1 << 23;
+        const MOVE_MEMBERS = 1 << 24;
+        const USE_VAD = 1 << 25;
+        const CHANGE_NICKNAME = 1 << 26;
+        const MANAGE_NICKNAMES = 1 << 27;
+        const MANAGE_ROLES = 1 << 28;
+        const MANAGE_WEBHOOKS = 1 << 29;
+        const MANAGE_EMOJIS_AND_STICKERS = 1 << 30;
+        const USE_APPLICATION_COMMANDS = 1 << 31;
+        const REQUEST_TO_SPEAK = 1 << 32;
+        const MANAGE_EVENTS = 1 << 33;
+        const MANAGE_THREADS = 1 << 34;
+        const CREATE_PUBLIC_THREADS = 1 << 35;
+        const CREATE_PRIVATE_THREADS = 1 << 36;
+        const USE_EXTERNAL_STICKERS = 1 << 37;
+        const SEND_MESSAGES_IN_THREADS = 1 << 38;
+        const START_EMBEDDED_ACTIVITIES = 1 << 39;
+        const MODERATE_MEMBERS = 1 << 40;
+    }
+}
+
+impl Default for PermissionSet {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl From<u64> for PermissionSet {
+    fn from(bits: u64) -> Self {
+        Self { bits }
+    }
+}
+
+impl From<PermissionSet> for u64 {
+    fn from(permissions: PermissionSet) -> Self {
+        permissions.bits()
+    }
+}
+
+impl From<Permissions> for PermissionSet {
+    fn from(permission: Permissions) -> Self {
+        Self::from_bits_truncate(1 << permission as u64)
+    }
+}
+
+impl From<Vec<Permissions>> for PermissionSet {
+    fn from(permissions: Vec<Permissions>) -> Self {
+        permissions
+            .into_iter()
+            .fold(Self::empty(), |set, permission| set | permission.into())
+    }
+}
+
+/// Only bits with a matching [`Permissions`] variant are kept.
+impl From<PermissionSet> for Vec<Permissions> {
+    fn from(permissions: PermissionSet) -> Self {
+        Permissions::ALL
+            .iter()
+            .copied()
+            .filter(|permission| permissions.contains((*permission).into()))
+            .collect()
+    }
+}
+
+bitflags::bitflags! {
+    /// Administrative functions a bot can run, so a bot only gets the access it needs.
+    #[derive(Encode, Decode, TypeInfo, MaxEncodedLen)]
+    pub struct BotCapabilities: u32 {
+        /// Update the guild's details.
+        const MANAGE_GUILD = 1 << 0;
+        /// Add members and mirror members leaving the guild.
+        const MANAGE_MEMBERS = 1 << 1;
+        const KICK_MEMBERS = 1 << 2;
+        const BAN_MEMBERS = 1 << 3;
+        /// Mute, deafen and time out members.
+        const MODERATE_MEMBERS = 1 << 4;
+        /// Create, update, delete, assign and revoke roles.
+        const MANAGE_ROLES = 1 << 5;
+        const MANAGE_CHANNELS = 1 << 6;
+        /// Attest to the Discord user an account is linked to.
+        const LINK_ACCOUNTS = 1 << 7;
+    }
+}
+
+impl Default for BotCapabilities {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
@@ -0,0 +1,3363 @@
+use crate::{mock::*, primitives::*, Error};
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::{Currency, GenesisBuild, Hooks},
+};
+use sp_core::{sr25519, Pair};
+use sp_runtime::traits::{BadOrigin, BlakeTwo256, Hash};
+
+type DiscordEvent = crate::Event<Test>;
+
+fn guild_input(name: &[u8]) -> crate::GuildInputOf<Test> {
+    Guild {
+        id: GUILD,
+        name: name.to_vec(),
+        icon: b"icon".to_vec(),
+        owner_id: 10,
+        afk_channel_id: 11,
+        afk_timeout: 300,
+        verification_level: 1,
+        default_message_notifications: 0,
+        explicit_content_filter: 0,
+        rules_channel_id: 12,
+        system_channel_id: 13,
+    }
+}
+
+/// Registers `GUILD` with `bot()` as its bot and an @everyone role granting nothing.
+fn setup_guild() {
+    assert_ok!(Discord::register_guild(
+        Origin::root(),
+        guild_input(b"Society")
+    ));
+    register_bot(bot(), BotCapabilities::all());
+    assert_ok!(Discord::create_role(
+        Origin::signed(bot()),
+        GUILD,
+        Some(GUILD),
+        b"@everyone".to_vec(),
+        0,
+        false,
+        0,
+        PermissionSet::empty(),
+        false,
+    ));
+}
+
+fn register_bot(account: AccountId, capabilities: BotCapabilities) {
+    assert_ok!(Discord::register_bot(
+        Origin::root(),
+        GUILD,
+        account,
+        b"bot".to_vec(),
+        100,
+        capabilities
+    ));
+}
+
+fn name_of(name: &[u8]) -> crate::NameOf<Test> {
+    name.to_vec().try_into().unwrap()
+}
+
+fn add_member(member: AccountId) {
+    assert_ok!(Discord::add_member(
+        Origin::signed(bot()),
+        GUILD,
+        member,
+        false,
+        false
+    ));
+}
+
+/// Creates a role through the bot, returning its index.
+fn create_role(id: Option<u64>, position: u64, permissions: PermissionSet) -> RoleIndex {
+    let index = Discord::next_role_index(GUILD);
+
+    assert_ok!(Discord::create_role(
+        Origin::signed(bot()),
+        GUILD,
+        id,
+        format!("role {}", index).into_bytes(),
+        0,
+        false,
+        position,
+        permissions,
+        false,
+    ));
+
+    index
+}
+
+/// Adds `member` holding a fresh role at `position` granting `permissions`.
+fn add_member_with_role(member: AccountId, position: u64, permissions: PermissionSet) -> RoleIndex {
+    add_member(member.clone());
+
+    let role = create_role(None, position, permissions);
+    assert_ok!(Discord::assign_role(
+        Origin::signed(bot()),
+        GUILD,
+        member,
+        RoleRef::Index(role)
+    ));
+
+    role
+}
+
+fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        System::set_block_number(System::block_number() + 1);
+        Discord::on_initialize(System::block_number());
+    }
+}
+
+fn link_signature(pair: &sr25519::Pair, discord_user: u64) -> Signature {
+    let account = AccountId::from(pair.public());
+
+    pair.sign(&Discord::link_challenge(&account, discord_user))
+        .into()
+}
+
+#[test]
+fn register_guild_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Discord::register_guild(
+            Origin::root(),
+            guild_input(b"Society")
+        ));
+
+        let guild = Discord::get_guild(GUILD).unwrap();
+        assert_eq!(guild.name.into_inner(), b"Society".to_vec());
+        assert_eq!(guild.owner_id, 10);
+        System::assert_last_event(DiscordEvent::GuildRegistered(GUILD).into());
+    });
+}
+
+#[test]
+fn register_guild_requires_root() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Discord::register_guild(Origin::signed(alice()), guild_input(b"Society")),
+            BadOrigin
+        );
+    });
+}
+
+#[test]
+fn register_guild_rejects_duplicates_and_long_names() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Discord::register_guild(Origin::root(), guild_input(&[b'x'; 17])),
+            Error::<Test>::NameTooLong
+        );
+
+        setup_guild();
+
+        assert_noop!(
+            Discord::register_guild(Origin::root(), guild_input(b"Society")),
+            Error::<Test>::GuildAlreadyRegistered
+        );
+    });
+}
+
+#[test]
+fn update_guild_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+
+        assert_ok!(Discord::update_guild(
+            Origin::signed(bot()),
+            guild_input(b"Renamed")
+        ));
+
+        assert_eq!(
+            Discord::get_guild(GUILD).unwrap().name.into_inner(),
+            b"Renamed".to_vec()
+        );
+        System::assert_last_event(DiscordEvent::GuildUpdated(GUILD).into());
+    });
+}
+
+#[test]
+fn update_guild_requires_bot() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+
+        assert_noop!(
+            Discord::update_guild(Origin::signed(alice()), guild_input(b"Renamed")),
+            Error::<Test>::NoPermission
+        );
+    });
+}
+
+#[test]
+fn register_bot_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+
+        register_bot(alice(), BotCapabilities::MANAGE_MEMBERS);
+
+        assert_eq!(
+            Discord::get_bot(GUILD, alice()),
+            Some(BotInfo {
+                name: name_of(b"bot"),
+                application_id: 100,
+                capabilities: BotCapabilities::MANAGE_MEMBERS,
+            })
+        );
+        System::assert_last_event(
+            DiscordEvent::BotAdded(GUILD, alice(), 100, BotCapabilities::MANAGE_MEMBERS).into(),
+        );
+
+        let mut bots = Discord::bots(GUILD);
+        bots.sort_by_key(|(account, _)| account.clone());
+        assert_eq!(
+            bots.into_iter()
+                .map(|(account, bot)| (account, bot.capabilities))
+                .collect::<Vec<_>>(),
+            vec![
+                (bot(), BotCapabilities::all()),
+                (alice(), BotCapabilities::MANAGE_MEMBERS)
+            ]
+        );
+    });
+}
+
+#[test]
+fn register_bot_requires_registered_guild() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Discord::register_bot(
+                Origin::root(),
+                GUILD,
+                bot(),
+                b"bot".to_vec(),
+                100,
+                BotCapabilities::all()
+            ),
+            Error::<Test>::GuildNotRegistered
+        );
+        assert_noop!(
+            Discord::register_bot(
+                Origin::signed(bot()),
+                GUILD,
+                bot(),
+                b"bot".to_vec(),
+                100,
+                BotCapabilities::all()
+            ),
+            BadOrigin
+        );
+    });
+}
+
+#[test]
+fn register_bot_rejects_existing_bots_and_long_names() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+
+        assert_noop!(
+            Discord::register_bot(
+                Origin::root(),
+                GUILD,
+                bot(),
+                b"bot".to_vec(),
+                100,
+                BotCapabilities::empty()
+            ),
+            Error::<Test>::BotAlreadyRegistered
+        );
+        assert_noop!(
+            Discord::register_bot(
+                Origin::root(),
+                GUILD,
+                alice(),
+                vec![b'x'; 17],
+                100,
+                BotCapabilities::all()
+            ),
+            Error::<Test>::NameTooLong
+        );
+    });
+}
+
+#[test]
+fn unregister_bot_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+
+        assert_noop!(
+            Discord::unregister_bot(Origin::signed(bot()), GUILD, bot()),
+            BadOrigin
+        );
+        assert_ok!(Discord::unregister_bot(Origin::root(), GUILD, bot()));
+
+        assert!(Discord::get_bot(GUILD, bot()).is_none());
+        System::assert_last_event(DiscordEvent::BotRemoved(GUILD, bot()).into());
+        assert_noop!(
+            Discord::add_member(Origin::signed(bot()), GUILD, alice(), false, false),
+            Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            Discord::unregister_bot(Origin::root(), GUILD, bot()),
+            Error::<Test>::NotABot
+        );
+    });
+}
+
+#[test]
+fn bots_are_limited_to_their_capabilities() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        register_bot(charlie(), BotCapabilities::MANAGE_MEMBERS);
+
+        assert_ok!(Discord::add_member(
+            Origin::signed(charlie()),
+            GUILD,
+            alice(),
+            false,
+            false
+        ));
+        assert_noop!(
+            Discord::ban_member(Origin::signed(charlie()), GUILD, alice(), vec![], None),
+            Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            Discord::create_channel(
+                Origin::signed(charlie()),
+                GUILD,
+                20,
+                ChannelType::GUILD_TEXT,
+                0,
+                vec![]
+            ),
+            Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            Discord::update_guild(Origin::signed(charlie()), guild_input(b"Renamed")),
+            Error::<Test>::NoPermission
+        );
+    });
+}
+
+#[test]
+fn set_bot_threshold_works() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Discord::set_bot_threshold(Origin::root(), GUILD, 2),
+            Error::<Test>::GuildNotRegistered
+        );
+
+        setup_guild();
+
+        assert_noop!(
+            Discord::set_bot_threshold(Origin::signed(bot()), GUILD, 2),
+            BadOrigin
+        );
+        assert_noop!(
+            Discord::set_bot_threshold(Origin::root(), GUILD, 4),
+            Error::<Test>::ThresholdTooHigh
+        );
+        assert_ok!(Discord::set_bot_threshold(Origin::root(), GUILD, 2));
+
+        assert_eq!(Discord::bot_threshold(GUILD), 2);
+        System::assert_last_event(DiscordEvent::BotThresholdSet(GUILD, 2).into());
+    });
+}
+
+#[test]
+fn bot_actions_wait_for_the_threshold() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+        register_bot(charlie(), BotCapabilities::all());
+        assert_ok!(Discord::set_bot_threshold(Origin::root(), GUILD, 2));
+
+        let ban = |bot: AccountId, reason: &[u8]| {
+            Discord::ban_member(Origin::signed(bot), GUILD, alice(), reason.to_vec(), None)
+        };
+        let hash = BlakeTwo256::hash_of(&crate::Call::<Test>::ban_member {
+            guild: GUILD,
+            member: alice(),
+            reason: b"spam".to_vec(),
+            expires_at: None,
+        });
+
+        assert_ok!(ban(bot(), b"spam"));
+        assert!(Discord::get_member(GUILD, alice()).is_some());
+        assert_eq!(
+            Discord::bot_approvals(GUILD, hash)
+                .unwrap()
+                .approvals
+                .into_inner(),
+            vec![bot()]
+        );
+        System::assert_last_event(DiscordEvent::BotActionApproved(GUILD, bot(), hash, 1).into());
+        assert_noop!(ban(bot(), b"spam"), Error::<Test>::AlreadyApproved);
+
+        // A different action doesn't count towards the first one.
+        assert_ok!(ban(charlie(), b"raid"));
+        assert!(Discord::get_member(GUILD, alice()).is_some());
+
+        assert_ok!(ban(charlie(), b"spam"));
+        assert!(Discord::get_banned_member(GUILD, alice()).is_some());
+        assert!(Discord::bot_approvals(GUILD, hash).is_none());
+        System::assert_last_event(
+            DiscordEvent::MemberBanned(GUILD, alice(), b"spam".to_vec(), None).into(),
+        );
+    });
+}
+
+#[test]
+fn bot_proposals_expire() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        register_bot(charlie(), BotCapabilities::all());
+        assert_ok!(Discord::set_bot_threshold(Origin::root(), GUILD, 2));
+
+        let add =
+            |bot: AccountId| Discord::add_member(Origin::signed(bot), GUILD, alice(), false, false);
+        let hash = BlakeTwo256::hash_of(&crate::Call::<Test>::add_member {
+            guild: GUILD,
+            member: alice(),
+            deaf: false,
+            mute: false,
+        });
+
+        assert_ok!(add(bot()));
+        assert_eq!(Discord::bot_approvals(GUILD, hash).unwrap().expires_at, 6);
+        // Expiry doesn't take up room in the scheduler.
+        assert!(Discord::scheduled(6).is_empty());
+
+        run_to_block(6);
+
+        // The expired approval doesn't count towards the threshold anymore.
+        assert_ok!(add(charlie()));
+        assert!(Discord::get_member(GUILD, alice()).is_none());
+        System::assert_has_event(DiscordEvent::BotProposalExpired(GUILD, hash).into());
+        assert_eq!(
+            Discord::bot_approvals(GUILD, hash)
+                .unwrap()
+                .approvals
+                .into_inner(),
+            vec![charlie()]
+        );
+    });
+}
+
+#[test]
+fn bots_can_only_keep_a_few_proposals_open() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        register_bot(charlie(), BotCapabilities::all());
+        assert_ok!(Discord::set_bot_threshold(Origin::root(), GUILD, 2));
+
+        let add = |bot: AccountId, member: AccountId| {
+            Discord::add_member(Origin::signed(bot), GUILD, member, false, false)
+        };
+        let hash = |member: AccountId| {
+            BlakeTwo256::hash_of(&crate::Call::<Test>::add_member {
+                guild: GUILD,
+                member,
+                deaf: false,
+                mute: false,
+            })
+        };
+
+        assert_ok!(add(bot(), alice()));
+        assert_ok!(add(bot(), bob()));
+        assert_noop!(add(bot(), charlie()), Error::<Test>::TooManyBotProposals);
+
+        // Proposals that ran no longer count.
+        assert_ok!(add(charlie(), alice()));
+        assert!(Discord::get_member(GUILD, alice()).is_some());
+        assert_ok!(add(bot(), charlie()));
+        assert_eq!(
+            Discord::open_bot_proposals(GUILD, bot()).into_inner(),
+            vec![hash(bob()), hash(charlie())]
+        );
+
+        // Neither do expired ones, which are removed to make room.
+        run_to_block(7);
+
+        assert_ok!(add(bot(), account(5)));
+        assert!(Discord::bot_approvals(GUILD, hash(bob())).is_none());
+        System::assert_has_event(DiscordEvent::BotProposalExpired(GUILD, hash(bob())).into());
+        assert_eq!(
+            Discord::open_bot_proposals(GUILD, bot()).into_inner(),
+            vec![hash(account(5))]
+        );
+    });
+}
+
+#[test]
+fn unregistering_a_bot_drops_its_proposals() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        register_bot(charlie(), BotCapabilities::all());
+        assert_ok!(Discord::set_bot_threshold(Origin::root(), GUILD, 3));
+
+        let add = |bot: AccountId, member: AccountId| {
+            Discord::add_member(Origin::signed(bot), GUILD, member, false, false)
+        };
+        let hash = |member: AccountId| {
+            BlakeTwo256::hash_of(&crate::Call::<Test>::add_member {
+                guild: GUILD,
+                member,
+                deaf: false,
+                mute: false,
+            })
+        };
+
+        assert_ok!(add(bot(), alice()));
+        assert_ok!(add(bot(), bob()));
+        assert_ok!(add(charlie(), bob()));
+
+        assert_ok!(Discord::unregister_bot(Origin::root(), GUILD, bot()));
+
+        assert!(Discord::bot_approvals(GUILD, hash(alice())).is_none());
+        assert_eq!(
+            Discord::bot_approvals(GUILD, hash(bob()))
+                .unwrap()
+                .approvals
+                .into_inner(),
+            vec![charlie()]
+        );
+        assert!(Discord::open_bot_proposals(GUILD, bot()).is_empty());
+    });
+}
+
+#[test]
+fn bot_threshold_leaves_members_alone() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member_with_role(alice(), 2, PermissionSet::KICK_MEMBERS);
+        add_member(bob());
+        assert_ok!(Discord::set_bot_threshold(Origin::root(), GUILD, 2));
+
+        assert_ok!(Discord::kick_member(Origin::signed(alice()), GUILD, bob()));
+
+        assert!(Discord::get_member(GUILD, bob()).is_none());
+    });
+}
+
+#[test]
+fn bot_approvals_drop_unregistered_bots() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        register_bot(alice(), BotCapabilities::all());
+        register_bot(charlie(), BotCapabilities::all());
+        assert_ok!(Discord::set_bot_threshold(Origin::root(), GUILD, 2));
+
+        assert_ok!(Discord::add_member(
+            Origin::signed(alice()),
+            GUILD,
+            bob(),
+            false,
+            false
+        ));
+        assert_ok!(Discord::unregister_bot(Origin::root(), GUILD, alice()));
+        assert_ok!(Discord::add_member(
+            Origin::signed(charlie()),
+            GUILD,
+            bob(),
+            false,
+            false
+        ));
+
+        assert!(Discord::get_member(GUILD, bob()).is_none());
+    });
+}
+
+#[test]
+fn failed_bot_action_keeps_approvals() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        register_bot(charlie(), BotCapabilities::all());
+        assert_ok!(Discord::set_bot_threshold(Origin::root(), GUILD, 2));
+
+        assert_ok!(Discord::kick_member(Origin::signed(bot()), GUILD, bob()));
+        assert_noop!(
+            Discord::kick_member(Origin::signed(charlie()), GUILD, bob()),
+            Error::<Test>::NotAMemberOfTheGuild
+        );
+    });
+}
+
+#[test]
+fn add_member_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+
+        assert_ok!(Discord::add_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            true,
+            false
+        ));
+
+        let member = Discord::get_member(GUILD, alice()).unwrap();
+        assert!(member.deaf);
+        assert!(!member.mute);
+        System::assert_last_event(DiscordEvent::MemberAdded(GUILD, alice()).into());
+    });
+}
+
+#[test]
+fn add_member_requires_bot() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+
+        assert_noop!(
+            Discord::add_member(Origin::signed(alice()), GUILD, alice(), false, false),
+            Error::<Test>::NoPermission
+        );
+    });
+}
+
+#[test]
+fn add_member_rejects_existing_members() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = add_member_with_role(alice(), 1, PermissionSet::empty());
+
+        assert_noop!(
+            Discord::add_member(Origin::signed(bot()), GUILD, alice(), false, false),
+            Error::<Test>::AlreadyMember
+        );
+        assert_eq!(
+            Discord::get_member(GUILD, alice())
+                .unwrap()
+                .roles
+                .into_inner(),
+            vec![role]
+        );
+    });
+}
+
+#[test]
+fn ban_member_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = add_member_with_role(alice(), 1, PermissionSet::empty());
+
+        assert_ok!(Discord::ban_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            b"spam".to_vec(),
+            None
+        ));
+
+        assert!(Discord::get_member(GUILD, alice()).is_none());
+        let (member, reason, expires_at) = Discord::get_banned_member(GUILD, alice()).unwrap();
+        assert_eq!(member.roles.into_inner(), vec![role]);
+        assert_eq!(reason.into_inner(), b"spam".to_vec());
+        assert_eq!(expires_at, None);
+        System::assert_last_event(
+            DiscordEvent::MemberBanned(GUILD, alice(), b"spam".to_vec(), None).into(),
+        );
+
+        assert_noop!(
+            Discord::add_member(Origin::signed(bot()), GUILD, alice(), false, false),
+            Error::<Test>::MemberIsBanned
+        );
+    });
+}
+
+#[test]
+fn ban_member_checks_permission_and_hierarchy() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member_with_role(alice(), 2, Permissions::BAN_MEMBERS.into());
+        add_member_with_role(bob(), 3, PermissionSet::empty());
+        add_member_with_role(charlie(), 1, PermissionSet::empty());
+
+        assert_noop!(
+            Discord::ban_member(Origin::signed(bob()), GUILD, charlie(), vec![], None),
+            Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            Discord::ban_member(Origin::signed(alice()), GUILD, bob(), vec![], None),
+            Error::<Test>::MemberAboveActor
+        );
+
+        assert_ok!(Discord::ban_member(
+            Origin::signed(alice()),
+            GUILD,
+            charlie(),
+            vec![],
+            None
+        ));
+        assert!(Discord::get_banned_member(GUILD, charlie()).is_some());
+    });
+}
+
+#[test]
+fn ban_member_rejects_invalid_input() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+
+        assert_noop!(
+            Discord::ban_member(Origin::signed(bot()), GUILD, bob(), vec![], None),
+            Error::<Test>::NotAMemberOfTheGuild
+        );
+        assert_noop!(
+            Discord::ban_member(Origin::signed(bot()), GUILD, alice(), vec![b'x'; 33], None),
+            Error::<Test>::ReasonTooLong
+        );
+        assert_noop!(
+            Discord::ban_member(Origin::signed(bot()), GUILD, alice(), vec![], Some(1)),
+            Error::<Test>::ExpiryInThePast
+        );
+    });
+}
+
+#[test]
+fn bans_expire() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+
+        assert_ok!(Discord::ban_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            vec![],
+            Some(3)
+        ));
+
+        run_to_block(2);
+        assert!(Discord::get_banned_member(GUILD, alice()).is_some());
+
+        run_to_block(3);
+        assert!(Discord::get_banned_member(GUILD, alice()).is_none());
+        System::assert_last_event(DiscordEvent::BanExpired(GUILD, alice()).into());
+    });
+}
+
+#[test]
+fn lifted_bans_are_not_expired_again() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+
+        assert_ok!(Discord::ban_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            vec![],
+            Some(3)
+        ));
+        assert_ok!(Discord::unban_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            true
+        ));
+        assert_ok!(Discord::ban_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            vec![],
+            None
+        ));
+
+        run_to_block(3);
+        assert!(Discord::get_banned_member(GUILD, alice()).is_some());
+    });
+}
+
+#[test]
+fn scheduling_spills_into_following_blocks() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+
+        for seed in 10..13 {
+            add_member(account(seed));
+            assert_ok!(Discord::ban_member(
+                Origin::signed(bot()),
+                GUILD,
+                account(seed),
+                vec![],
+                Some(3)
+            ));
+        }
+
+        assert_eq!(Discord::scheduled(3).len(), 2);
+        assert_eq!(Discord::scheduled(4).len(), 1);
+
+        run_to_block(4);
+        for seed in 10..13 {
+            assert!(Discord::get_banned_member(GUILD, account(seed)).is_none());
+        }
+    });
+}
+
+#[test]
+fn scheduling_fails_when_every_block_is_full() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+
+        for seed in 10..30 {
+            add_member(account(seed));
+            assert_ok!(Discord::ban_member(
+                Origin::signed(bot()),
+                GUILD,
+                account(seed),
+                vec![],
+                Some(3)
+            ));
+        }
+        add_member(alice());
+
+        assert_noop!(
+            Discord::ban_member(Origin::signed(bot()), GUILD, alice(), vec![], Some(3)),
+            Error::<Test>::SchedulerFull
+        );
+    });
+}
+
+#[test]
+fn unban_member_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = add_member_with_role(alice(), 1, PermissionSet::empty());
+        add_member(bob());
+
+        assert_ok!(Discord::ban_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            vec![],
+            None
+        ));
+        assert_ok!(Discord::ban_member(
+            Origin::signed(bot()),
+            GUILD,
+            bob(),
+            vec![],
+            None
+        ));
+
+        assert_ok!(Discord::unban_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            true
+        ));
+        assert_eq!(
+            Discord::get_member(GUILD, alice())
+                .unwrap()
+                .roles
+                .into_inner(),
+            vec![role]
+        );
+        System::assert_last_event(DiscordEvent::MemberUnbanned(GUILD, alice(), true).into());
+
+        assert_ok!(Discord::unban_member(
+            Origin::signed(bot()),
+            GUILD,
+            bob(),
+            false
+        ));
+        assert!(Discord::get_member(GUILD, bob()).is_none());
+        assert!(Discord::get_banned_member(GUILD, bob()).is_none());
+        System::assert_last_event(DiscordEvent::MemberUnbanned(GUILD, bob(), false).into());
+
+        assert_noop!(
+            Discord::unban_member(Origin::signed(bot()), GUILD, bob(), false),
+            Error::<Test>::NotBanned
+        );
+        assert_noop!(
+            Discord::unban_member(Origin::signed(alice()), GUILD, bob(), false),
+            Error::<Test>::NoPermission
+        );
+    });
+}
+
+#[test]
+fn kick_member_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member_with_role(alice(), 2, Permissions::KICK_MEMBERS.into());
+        add_member(bob());
+
+        assert_ok!(Discord::kick_member(Origin::signed(alice()), GUILD, bob()));
+
+        assert!(Discord::get_member(GUILD, bob()).is_none());
+        System::assert_last_event(DiscordEvent::MemberKicked(GUILD, bob()).into());
+
+        assert_noop!(
+            Discord::kick_member(Origin::signed(alice()), GUILD, bob()),
+            Error::<Test>::NotAMemberOfTheGuild
+        );
+        assert_noop!(
+            Discord::kick_member(Origin::signed(charlie()), GUILD, alice()),
+            Error::<Test>::NoPermission
+        );
+    });
+}
+
+#[test]
+fn set_mute_and_deaf_work() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+
+        assert_ok!(Discord::set_mute(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            true
+        ));
+        System::assert_last_event(DiscordEvent::MemberMuted(GUILD, alice(), true).into());
+
+        assert_ok!(Discord::set_deaf(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            true
+        ));
+        System::assert_last_event(DiscordEvent::MemberDeafened(GUILD, alice(), true).into());
+
+        let member = Discord::get_member(GUILD, alice()).unwrap();
+        assert!(member.mute);
+        assert!(member.deaf);
+
+        assert_noop!(
+            Discord::set_mute(Origin::signed(alice()), GUILD, alice(), false),
+            Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            Discord::set_deaf(Origin::signed(bot()), GUILD, bob(), true),
+            Error::<Test>::NotAMemberOfTheGuild
+        );
+    });
+}
+
+#[test]
+fn timeouts_restrict_permissions_and_expire() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let permissions = PermissionSet::all() - PermissionSet::ADMINISTRATOR;
+        add_member_with_role(alice(), 1, permissions);
+
+        assert_ok!(Discord::timeout_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            Some(3)
+        ));
+        System::assert_last_event(DiscordEvent::MemberTimedOut(GUILD, alice(), Some(3)).into());
+
+        assert_eq!(
+            Discord::effective_permissions(GUILD, &alice(), None),
+            PermissionSet::VIEW_CHANNEL | PermissionSet::READ_MESSAGE_HISTORY
+        );
+        assert_noop!(
+            Discord::kick_member(Origin::signed(alice()), GUILD, bob()),
+            Error::<Test>::NoPermission
+        );
+
+        run_to_block(3);
+
+        assert_eq!(
+            Discord::get_member(GUILD, alice())
+                .unwrap()
+                .communication_disabled_until,
+            None
+        );
+        System::assert_last_event(DiscordEvent::TimeoutExpired(GUILD, alice()).into());
+        assert_eq!(
+            Discord::effective_permissions(GUILD, &alice(), None),
+            permissions
+        );
+    });
+}
+
+#[test]
+fn timeout_member_rejects_invalid_input() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+
+        assert_noop!(
+            Discord::timeout_member(Origin::signed(bot()), GUILD, alice(), Some(1)),
+            Error::<Test>::ExpiryInThePast
+        );
+        assert_noop!(
+            Discord::timeout_member(Origin::signed(bot()), GUILD, bob(), None),
+            Error::<Test>::NotAMemberOfTheGuild
+        );
+    });
+}
+
+#[test]
+fn remove_member_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+
+        assert_ok!(Discord::remove_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice()
+        ));
+
+        assert!(Discord::get_member(GUILD, alice()).is_none());
+        System::assert_last_event(DiscordEvent::MemberRemoved(GUILD, alice()).into());
+
+        assert_noop!(
+            Discord::remove_member(Origin::signed(bot()), GUILD, alice()),
+            Error::<Test>::NotAMemberOfTheGuild
+        );
+    });
+}
+
+#[test]
+fn create_role_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let index = Discord::next_role_index(GUILD);
+
+        assert_ok!(Discord::create_role(
+            Origin::signed(bot()),
+            GUILD,
+            Some(100),
+            b"mods".to_vec(),
+            0xff0000,
+            true,
+            5,
+            Permissions::KICK_MEMBERS.into(),
+            true,
+        ));
+
+        let role = Discord::get_role(GUILD, index).unwrap();
+        assert_eq!(role.id, Some(100));
+        assert_eq!(role.position, 5);
+        assert_eq!(Discord::get_role_index(GUILD, 100), Some(index));
+        assert_eq!(Discord::next_role_index(GUILD), index + 1);
+        System::assert_last_event(
+            DiscordEvent::RoleCreated(
+                GUILD,
+                index,
+                b"mods".to_vec(),
+                0xff0000,
+                true,
+                5,
+                Permissions::KICK_MEMBERS.into(),
+                true,
+            )
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn create_role_rejects_invalid_input() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        create_role(Some(100), 1, PermissionSet::empty());
+        add_member_with_role(alice(), 2, Permissions::MANAGE_ROLES.into());
+
+        assert_noop!(
+            Discord::create_role(
+                Origin::signed(bob()),
+                GUILD,
+                None,
+                b"role".to_vec(),
+                0,
+                false,
+                1,
+                PermissionSet::empty(),
+                false
+            ),
+            Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            Discord::create_role(
+                Origin::signed(alice()),
+                GUILD,
+                None,
+                b"role".to_vec(),
+                0,
+                false,
+                2,
+                PermissionSet::empty(),
+                false
+            ),
+            Error::<Test>::RoleAboveActor
+        );
+        assert_noop!(
+            Discord::create_role(
+                Origin::signed(bot()),
+                GUILD,
+                Some(100),
+                b"role".to_vec(),
+                0,
+                false,
+                1,
+                PermissionSet::empty(),
+                false
+            ),
+            Error::<Test>::RoleIdAlreadyInUse
+        );
+        assert_noop!(
+            Discord::create_role(
+                Origin::signed(bot()),
+                GUILD,
+                None,
+                vec![b'x'; 17],
+                0,
+                false,
+                1,
+                PermissionSet::empty(),
+                false
+            ),
+            Error::<Test>::NameTooLong
+        );
+    });
+}
+
+#[test]
+fn role_names_may_repeat() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = create_role(Some(100), 1, PermissionSet::empty());
+        let name = Discord::get_role(GUILD, role).unwrap().name.into_inner();
+        let other = Discord::next_role_index(GUILD);
+
+        assert_ok!(Discord::create_role(
+            Origin::signed(bot()),
+            GUILD,
+            Some(101),
+            name.clone(),
+            0,
+            false,
+            1,
+            PermissionSet::empty(),
+            false
+        ));
+        assert_ok!(Discord::update_role(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Index(role),
+            name.clone(),
+            1,
+            false,
+            1,
+            PermissionSet::empty(),
+            false
+        ));
+
+        assert_eq!(
+            Discord::get_role(GUILD, other).unwrap().name.into_inner(),
+            name
+        );
+
+        // Duplicates are told apart by their Discord id instead.
+        assert_noop!(
+            Discord::create_role(
+                Origin::signed(bot()),
+                GUILD,
+                Some(100),
+                b"other".to_vec(),
+                0,
+                false,
+                1,
+                PermissionSet::empty(),
+                false
+            ),
+            Error::<Test>::RoleIdAlreadyInUse
+        );
+    });
+}
+
+#[test]
+fn retrying_create_role_without_an_id_duplicates_the_role() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let first = Discord::next_role_index(GUILD);
+
+        for _ in 0..2 {
+            assert_ok!(Discord::create_role(
+                Origin::signed(bot()),
+                GUILD,
+                None,
+                b"retried".to_vec(),
+                0,
+                false,
+                1,
+                PermissionSet::empty(),
+                false
+            ));
+        }
+
+        let duplicate = first + 1;
+        assert_eq!(Discord::next_role_index(GUILD), duplicate + 1);
+        for role in [first, duplicate] {
+            let role = Discord::get_role(GUILD, role).unwrap();
+            assert_eq!(role.name.into_inner(), b"retried".to_vec());
+            assert_eq!(role.id, None);
+        }
+
+        // The bot drops the duplicate and confirms the role Discord created.
+        assert_ok!(Discord::delete_role(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Index(duplicate),
+            0
+        ));
+        assert_ok!(Discord::confirm_role_id(
+            Origin::signed(bot()),
+            GUILD,
+            first,
+            100
+        ));
+
+        assert_eq!(Discord::get_role(GUILD, duplicate), None);
+        assert_eq!(Discord::get_role_index(GUILD, 100), Some(first));
+    });
+}
+
+#[test]
+fn confirm_role_id_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = create_role(None, 1, PermissionSet::empty());
+        let confirmed = create_role(Some(100), 1, PermissionSet::empty());
+
+        assert_ok!(Discord::confirm_role_id(
+            Origin::signed(bot()),
+            GUILD,
+            role,
+            101
+        ));
+
+        assert_eq!(Discord::get_role(GUILD, role).unwrap().id, Some(101));
+        assert_eq!(Discord::get_role_index(GUILD, 101), Some(role));
+        System::assert_last_event(DiscordEvent::RoleIdConfirmed(GUILD, role, 101).into());
+
+        assert_noop!(
+            Discord::confirm_role_id(Origin::signed(bot()), GUILD, confirmed, 102),
+            Error::<Test>::RoleIdAlreadyConfirmed
+        );
+        assert_noop!(
+            Discord::confirm_role_id(Origin::signed(bot()), GUILD, 42, 102),
+            Error::<Test>::RoleDoesntExist
+        );
+        assert_noop!(
+            Discord::confirm_role_id(Origin::signed(alice()), GUILD, role, 102),
+            Error::<Test>::NoPermission
+        );
+    });
+}
+
+#[test]
+fn confirm_role_id_rejects_ids_in_use() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = create_role(None, 1, PermissionSet::empty());
+        create_role(Some(100), 1, PermissionSet::empty());
+
+        assert_noop!(
+            Discord::confirm_role_id(Origin::signed(bot()), GUILD, role, 100),
+            Error::<Test>::RoleIdAlreadyInUse
+        );
+    });
+}
+
+#[test]
+fn assign_role_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+        let role = create_role(Some(100), 1, PermissionSet::empty());
+
+        assert_ok!(Discord::assign_role(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            RoleRef::Id(100)
+        ));
+
+        assert_eq!(
+            Discord::get_member(GUILD, alice())
+                .unwrap()
+                .roles
+                .into_inner(),
+            vec![role]
+        );
+        System::assert_last_event(DiscordEvent::RoleAssigned(GUILD, alice(), role).into());
+    });
+}
+
+#[test]
+fn assign_role_rejects_invalid_input() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member_with_role(alice(), 2, Permissions::MANAGE_ROLES.into());
+        add_member(bob());
+        let high = create_role(None, 2, PermissionSet::empty());
+
+        assert_noop!(
+            Discord::assign_role(Origin::signed(bot()), GUILD, bob(), RoleRef::Index(42)),
+            Error::<Test>::RoleDoesntExist
+        );
+        assert_noop!(
+            Discord::assign_role(Origin::signed(bot()), GUILD, bob(), RoleRef::Id(42)),
+            Error::<Test>::RoleDoesntExist
+        );
+        assert_noop!(
+            Discord::assign_role(
+                Origin::signed(bot()),
+                GUILD,
+                charlie(),
+                RoleRef::Index(high)
+            ),
+            Error::<Test>::NotAMemberOfTheGuild
+        );
+        assert_noop!(
+            Discord::assign_role(Origin::signed(alice()), GUILD, bob(), RoleRef::Index(high)),
+            Error::<Test>::RoleAboveActor
+        );
+        assert_noop!(
+            Discord::assign_role(Origin::signed(bob()), GUILD, bob(), RoleRef::Index(high)),
+            Error::<Test>::NoPermission
+        );
+
+        assert_ok!(Discord::assign_role(
+            Origin::signed(bot()),
+            GUILD,
+            bob(),
+            RoleRef::Index(high)
+        ));
+        assert_noop!(
+            Discord::assign_role(Origin::signed(bot()), GUILD, bob(), RoleRef::Index(high)),
+            Error::<Test>::RoleAlreadyAssigned
+        );
+
+        for _ in 0..3 {
+            let role = create_role(None, 1, PermissionSet::empty());
+            assert_ok!(Discord::assign_role(
+                Origin::signed(bot()),
+                GUILD,
+                bob(),
+                RoleRef::Index(role)
+            ));
+        }
+        let extra = create_role(None, 1, PermissionSet::empty());
+        assert_noop!(
+            Discord::assign_role(Origin::signed(bot()), GUILD, bob(), RoleRef::Index(extra)),
+            Error::<Test>::TooManyRoles
+        );
+    });
+}
+
+#[test]
+fn update_role_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = create_role(Some(100), 1, PermissionSet::empty());
+
+        assert_ok!(Discord::update_role(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Id(100),
+            b"renamed".to_vec(),
+            1,
+            true,
+            3,
+            PermissionSet::all(),
+            true,
+        ));
+
+        let stored = Discord::get_role(GUILD, role).unwrap();
+        assert_eq!(stored.name.into_inner(), b"renamed".to_vec());
+        assert_eq!(stored.position, 3);
+        assert_eq!(stored.permissions, PermissionSet::all());
+        System::assert_last_event(
+            DiscordEvent::RoleUpdated(
+                GUILD,
+                role,
+                b"renamed".to_vec(),
+                1,
+                true,
+                3,
+                PermissionSet::all(),
+                true,
+            )
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn update_role_checks_hierarchy() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member_with_role(alice(), 2, Permissions::MANAGE_ROLES.into());
+        let role = create_role(None, 1, PermissionSet::empty());
+
+        assert_noop!(
+            Discord::update_role(
+                Origin::signed(alice()),
+                GUILD,
+                RoleRef::Index(role),
+                b"role".to_vec(),
+                0,
+                false,
+                2,
+                PermissionSet::empty(),
+                false
+            ),
+            Error::<Test>::RoleAboveActor
+        );
+        assert_noop!(
+            Discord::update_role(
+                Origin::signed(alice()),
+                GUILD,
+                RoleRef::Index(42),
+                b"role".to_vec(),
+                0,
+                false,
+                1,
+                PermissionSet::empty(),
+                false
+            ),
+            Error::<Test>::RoleDoesntExist
+        );
+
+        assert_ok!(Discord::update_role(
+            Origin::signed(alice()),
+            GUILD,
+            RoleRef::Index(role),
+            b"role".to_vec(),
+            0,
+            false,
+            1,
+            PermissionSet::empty(),
+            true
+        ));
+    });
+}
+
+#[test]
+fn members_cannot_grant_permissions_they_lack() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member_with_role(alice(), 3, Permissions::MANAGE_ROLES.into());
+        add_member(bob());
+        let admin = create_role(None, 1, Permissions::ADMINISTRATOR.into());
+        let everyone = Discord::everyone_role(GUILD).unwrap();
+
+        assert_noop!(
+            Discord::create_role(
+                Origin::signed(alice()),
+                GUILD,
+                None,
+                b"admin".to_vec(),
+                0,
+                false,
+                2,
+                Permissions::ADMINISTRATOR.into(),
+                false
+            ),
+            Error::<Test>::PermissionsAboveActor
+        );
+        assert_noop!(
+            Discord::update_role(
+                Origin::signed(alice()),
+                GUILD,
+                RoleRef::Index(everyone),
+                b"@everyone".to_vec(),
+                0,
+                false,
+                0,
+                Permissions::ADMINISTRATOR.into(),
+                false
+            ),
+            Error::<Test>::PermissionsAboveActor
+        );
+        assert_noop!(
+            Discord::assign_role(
+                Origin::signed(alice()),
+                GUILD,
+                alice(),
+                RoleRef::Index(admin)
+            ),
+            Error::<Test>::PermissionsAboveActor
+        );
+        assert_noop!(
+            Discord::set_token_gate(
+                Origin::signed(alice()),
+                GUILD,
+                RoleRef::Index(admin),
+                Some(0)
+            ),
+            Error::<Test>::PermissionsAboveActor
+        );
+        assert_noop!(
+            Discord::set_subscription_plan(
+                Origin::signed(alice()),
+                GUILD,
+                RoleRef::Index(admin),
+                Some(plan(0, 10, 5))
+            ),
+            Error::<Test>::PermissionsAboveActor
+        );
+
+        // Permissions the member holds can still be granted.
+        assert_ok!(Discord::create_role(
+            Origin::signed(alice()),
+            GUILD,
+            None,
+            b"helper".to_vec(),
+            0,
+            false,
+            2,
+            Permissions::MANAGE_ROLES.into(),
+            false
+        ));
+
+        // Administrators can grant anything below them.
+        add_member_with_role(charlie(), 3, Permissions::ADMINISTRATOR.into());
+        assert_ok!(Discord::assign_role(
+            Origin::signed(charlie()),
+            GUILD,
+            bob(),
+            RoleRef::Index(admin)
+        ));
+    });
+}
+
+#[test]
+fn delete_role_removes_references() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = add_member_with_role(alice(), 1, PermissionSet::empty());
+        add_member(bob());
+        assert_ok!(Discord::assign_role(
+            Origin::signed(bot()),
+            GUILD,
+            bob(),
+            RoleRef::Index(role)
+        ));
+        assert_ok!(Discord::ban_member(
+            Origin::signed(bot()),
+            GUILD,
+            bob(),
+            vec![],
+            None
+        ));
+        assert_ok!(Discord::create_channel(
+            Origin::signed(bot()),
+            GUILD,
+            50,
+            ChannelType::GUILD_TEXT,
+            0,
+            vec![PermissionOverwrite {
+                target: OverwriteTarget::Role(role),
+                allow: PermissionSet::all(),
+                deny: PermissionSet::empty(),
+            }],
+        ));
+
+        assert_noop!(
+            Discord::delete_role(Origin::signed(bot()), GUILD, RoleRef::Index(role), 2),
+            Error::<Test>::InvalidWitness
+        );
+
+        assert_ok!(Discord::delete_role(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Index(role),
+            3
+        ));
+
+        assert!(Discord::get_role(GUILD, role).is_none());
+        assert!(Discord::get_member(GUILD, alice())
+            .unwrap()
+            .roles
+            .is_empty());
+        assert!(Discord::get_banned_member(GUILD, bob())
+            .unwrap()
+            .0
+            .roles
+            .is_empty());
+        assert!(Discord::get_channel(GUILD, 50)
+            .unwrap()
+            .permission_overwrites
+            .is_empty());
+        System::assert_last_event(DiscordEvent::RoleDeleted(GUILD, role).into());
+    });
+}
+
+#[test]
+fn delete_role_frees_its_id() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        create_role(Some(100), 1, PermissionSet::empty());
+
+        assert_ok!(Discord::delete_role(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Id(100),
+            0
+        ));
+
+        assert_eq!(Discord::get_role_index(GUILD, 100), None);
+        assert_noop!(
+            Discord::delete_role(Origin::signed(bot()), GUILD, RoleRef::Id(100), 0),
+            Error::<Test>::RoleDoesntExist
+        );
+        assert_noop!(
+            Discord::delete_role(Origin::signed(alice()), GUILD, RoleRef::Id(100), 0),
+            Error::<Test>::NoPermission
+        );
+    });
+}
+
+#[test]
+fn revoke_role_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = add_member_with_role(alice(), 1, PermissionSet::empty());
+
+        assert_ok!(Discord::revoke_role(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            RoleRef::Index(role)
+        ));
+
+        assert!(Discord::get_member(GUILD, alice())
+            .unwrap()
+            .roles
+            .is_empty());
+        System::assert_last_event(DiscordEvent::RoleRevoked(GUILD, alice(), role).into());
+
+        assert_noop!(
+            Discord::revoke_role(Origin::signed(bot()), GUILD, alice(), RoleRef::Index(role)),
+            Error::<Test>::RoleNotAssigned
+        );
+        assert_noop!(
+            Discord::revoke_role(Origin::signed(bot()), GUILD, bob(), RoleRef::Index(role)),
+            Error::<Test>::NotAMemberOfTheGuild
+        );
+        assert_noop!(
+            Discord::revoke_role(
+                Origin::signed(alice()),
+                GUILD,
+                alice(),
+                RoleRef::Index(role)
+            ),
+            Error::<Test>::NoPermission
+        );
+    });
+}
+
+#[test]
+fn channels_work() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+
+        assert_ok!(Discord::create_channel(
+            Origin::signed(bot()),
+            GUILD,
+            50,
+            ChannelType::GUILD_TEXT,
+            0,
+            vec![]
+        ));
+        System::assert_last_event(
+            DiscordEvent::ChannelCreated(GUILD, 50, ChannelType::GUILD_TEXT, 0).into(),
+        );
+
+        assert_ok!(Discord::update_channel(
+            Origin::signed(bot()),
+            GUILD,
+            50,
+            ChannelType::GUILD_VOICE,
+            2,
+            vec![]
+        ));
+        assert_eq!(Discord::get_channel(GUILD, 50).unwrap().position, 2);
+        System::assert_last_event(
+            DiscordEvent::ChannelUpdated(GUILD, 50, ChannelType::GUILD_VOICE, 2).into(),
+        );
+
+        assert_ok!(Discord::delete_channel(Origin::signed(bot()), GUILD, 50));
+        assert!(Discord::get_channel(GUILD, 50).is_none());
+        System::assert_last_event(DiscordEvent::ChannelDeleted(GUILD, 50).into());
+    });
+}
+
+#[test]
+fn channels_reject_invalid_input() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let overwrite = PermissionOverwrite {
+            target: OverwriteTarget::Member(alice()),
+            allow: PermissionSet::empty(),
+            deny: PermissionSet::empty(),
+        };
+
+        assert_noop!(
+            Discord::create_channel(
+                Origin::signed(bot()),
+                GUILD,
+                50,
+                ChannelType::GUILD_TEXT,
+                0,
+                vec![overwrite; 5]
+            ),
+            Error::<Test>::TooManyOverwrites
+        );
+        assert_noop!(
+            Discord::create_channel(
+                Origin::signed(alice()),
+                GUILD,
+                50,
+                ChannelType::GUILD_TEXT,
+                0,
+                vec![]
+            ),
+            Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            Discord::update_channel(
+                Origin::signed(bot()),
+                GUILD,
+                50,
+                ChannelType::GUILD_TEXT,
+                0,
+                vec![]
+            ),
+            Error::<Test>::ChannelDoesntExist
+        );
+        assert_noop!(
+            Discord::delete_channel(Origin::signed(bot()), GUILD, 50),
+            Error::<Test>::ChannelDoesntExist
+        );
+
+        assert_ok!(Discord::create_channel(
+            Origin::signed(bot()),
+            GUILD,
+            50,
+            ChannelType::GUILD_TEXT,
+            0,
+            vec![]
+        ));
+        assert_noop!(
+            Discord::create_channel(
+                Origin::signed(bot()),
+                GUILD,
+                50,
+                ChannelType::GUILD_TEXT,
+                0,
+                vec![]
+            ),
+            Error::<Test>::ChannelAlreadyExists
+        );
+    });
+}
+
+#[test]
+fn permissions_map_to_discord_bits() {
+    for (bit, permission) in Permissions::ALL.iter().enumerate() {
+        assert_eq!(*permission as usize, bit);
+        assert_eq!(u64::from(PermissionSet::from(*permission)), 1 << bit);
+    }
+
+    // A few values straight from Discord's documentation.
+    assert_eq!(
+        u64::from(PermissionSet::from(Permissions::ADMINISTRATOR)),
+        0x8
+    );
+    assert_eq!(
+        u64::from(PermissionSet::from(Permissions::SEND_MESSAGES)),
+        0x800
+    );
+    assert_eq!(
+        u64::from(PermissionSet::from(Permissions::MANAGE_ROLES)),
+        0x1000_0000
+    );
+    assert_eq!(
+        u64::from(PermissionSet::from(Permissions::MODERATE_MEMBERS)),
+        0x100_0000_0000
+    );
+    assert_eq!(u64::from(PermissionSet::all()), (1 << 41) - 1);
+}
+
+#[test]
+fn permission_sets_round_trip_raw_bits() {
+    use codec::{Decode, Encode};
+
+    // Bits Discord may add later have no flag yet, and are kept anyway.
+    for bits in [0, 0x8, (1 << 41) - 1, 1 << 50, u64::MAX] {
+        let permissions = PermissionSet::from(bits);
+
+        assert_eq!(u64::from(permissions), bits);
+        assert_eq!(permissions.encode(), bits.encode());
+        assert_eq!(
+            PermissionSet::decode(&mut &bits.encode()[..]).unwrap(),
+            permissions
+        );
+    }
+}
+
+#[test]
+fn permission_lists_round_trip() {
+    let all: Vec<Permissions> = PermissionSet::all().into();
+    assert_eq!(all, Permissions::ALL.to_vec());
+    assert_eq!(PermissionSet::from(all), PermissionSet::all());
+
+    let list = vec![Permissions::BAN_MEMBERS, Permissions::MANAGE_ROLES];
+    let permissions = PermissionSet::from(list.clone());
+    assert_eq!(
+        permissions,
+        PermissionSet::BAN_MEMBERS | PermissionSet::MANAGE_ROLES
+    );
+    assert_eq!(Vec::<Permissions>::from(permissions), list);
+
+    // Lists only hold named permissions, so unknown bits are left out.
+    assert_eq!(
+        Vec::<Permissions>::from(PermissionSet::from(1 << 50 | 1 << 2)),
+        vec![Permissions::BAN_MEMBERS]
+    );
+    assert_eq!(
+        PermissionSet::from(Vec::<Permissions>::new()),
+        PermissionSet::empty()
+    );
+}
+
+#[test]
+fn effective_permissions_combine_roles() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        assert_ok!(Discord::update_role(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Id(GUILD),
+            b"@everyone".to_vec(),
+            0,
+            false,
+            0,
+            Permissions::VIEW_CHANNEL.into(),
+            false,
+        ));
+        add_member_with_role(alice(), 1, Permissions::KICK_MEMBERS.into());
+        add_member_with_role(bob(), 1, Permissions::ADMINISTRATOR.into());
+
+        assert_eq!(
+            Discord::effective_permissions(GUILD, &alice(), None),
+            PermissionSet::VIEW_CHANNEL | PermissionSet::KICK_MEMBERS
+        );
+        assert_eq!(
+            Discord::effective_permissions(GUILD, &bob(), None),
+            PermissionSet::all()
+        );
+        assert_eq!(
+            Discord::effective_permissions(GUILD, &charlie(), None),
+            PermissionSet::empty()
+        );
+    });
+}
+
+#[test]
+fn effective_permissions_apply_channel_overwrites() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = add_member_with_role(
+            alice(),
+            1,
+            PermissionSet::VIEW_CHANNEL | PermissionSet::SEND_MESSAGES,
+        );
+        let everyone = Discord::everyone_role(GUILD).unwrap();
+
+        assert_ok!(Discord::create_channel(
+            Origin::signed(bot()),
+            GUILD,
+            50,
+            ChannelType::GUILD_TEXT,
+            0,
+            vec![
+                PermissionOverwrite {
+                    target: OverwriteTarget::Member(alice()),
+                    allow: Permissions::ATTACH_FILES.into(),
+                    deny: PermissionSet::empty(),
+                },
+                PermissionOverwrite {
+                    target: OverwriteTarget::Role(role),
+                    allow: Permissions::EMBED_LINKS.into(),
+                    deny: Permissions::SEND_MESSAGES.into(),
+                },
+                PermissionOverwrite {
+                    target: OverwriteTarget::Role(everyone),
+                    allow: PermissionSet::empty(),
+                    deny: Permissions::VIEW_CHANNEL.into(),
+                },
+            ],
+        ));
+
+        assert_eq!(
+            Discord::effective_permissions(GUILD, &alice(), Some(50)),
+            PermissionSet::EMBED_LINKS | PermissionSet::ATTACH_FILES
+        );
+        assert!(!Discord::has_permission(
+            GUILD,
+            &alice(),
+            Some(50),
+            Permissions::SEND_MESSAGES
+        ));
+        assert!(Discord::has_permission(
+            GUILD,
+            &alice(),
+            None,
+            Permissions::SEND_MESSAGES
+        ));
+    });
+}
+
+#[test]
+fn link_account_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let pair = sr25519::Pair::from_seed(&[7; 32]);
+        let account = AccountId::from(pair.public());
+
+        assert_ok!(Discord::link_account(
+            Origin::signed(bot()),
+            GUILD,
+            account.clone(),
+            500,
+            link_signature(&pair, 500)
+        ));
+
+        assert_eq!(Discord::discord_user_of(&account), Some(500));
+        assert_eq!(Discord::account_of_discord_user(500), Some(account.clone()));
+        assert_eq!(Discord::link_nonce(&account), 1);
+        System::assert_last_event(DiscordEvent::AccountLinked(account.clone(), 500).into());
+
+        assert_ok!(Discord::link_account(
+            Origin::signed(bot()),
+            GUILD,
+            account.clone(),
+            501,
+            link_signature(&pair, 501)
+        ));
+
+        assert_eq!(Discord::discord_user_of(&account), Some(501));
+        assert_eq!(Discord::account_of_discord_user(500), None);
+        System::assert_last_event(DiscordEvent::AccountRelinked(account, 500, 501).into());
+    });
+}
+
+#[test]
+fn link_account_rejects_discord_users_linked_elsewhere() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let old = sr25519::Pair::from_seed(&[7; 32]);
+        let new = sr25519::Pair::from_seed(&[8; 32]);
+
+        assert_ok!(Discord::link_account(
+            Origin::signed(bot()),
+            GUILD,
+            AccountId::from(old.public()),
+            500,
+            link_signature(&old, 500)
+        ));
+        assert_noop!(
+            Discord::link_account(
+                Origin::signed(bot()),
+                GUILD,
+                AccountId::from(new.public()),
+                500,
+                link_signature(&new, 500)
+            ),
+            Error::<Test>::DiscordUserAlreadyLinked
+        );
+
+        // Once the current account lets go of the Discord user, it can be linked elsewhere.
+        assert_ok!(Discord::unlink_account(Origin::signed(AccountId::from(
+            old.public()
+        ))));
+        assert_ok!(Discord::link_account(
+            Origin::signed(bot()),
+            GUILD,
+            AccountId::from(new.public()),
+            500,
+            link_signature(&new, 500)
+        ));
+
+        assert_eq!(
+            Discord::account_of_discord_user(500),
+            Some(AccountId::from(new.public()))
+        );
+    });
+}
+
+#[test]
+fn link_account_rejects_invalid_signatures() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let pair = sr25519::Pair::from_seed(&[7; 32]);
+        let account = AccountId::from(pair.public());
+        let signature = link_signature(&pair, 500);
+
+        assert_noop!(
+            Discord::link_account(
+                Origin::signed(bot()),
+                GUILD,
+                account.clone(),
+                501,
+                signature.clone()
+            ),
+            Error::<Test>::InvalidLinkSignature
+        );
+        assert_noop!(
+            Discord::link_account(
+                Origin::signed(alice()),
+                GUILD,
+                account.clone(),
+                500,
+                signature.clone()
+            ),
+            Error::<Test>::NoPermission
+        );
+
+        assert_ok!(Discord::link_account(
+            Origin::signed(bot()),
+            GUILD,
+            account.clone(),
+            500,
+            signature.clone()
+        ));
+
+        // The nonce moved on, so the same signature can't be replayed.
+        assert_noop!(
+            Discord::link_account(
+                Origin::signed(bot()),
+                GUILD,
+                account.clone(),
+                500,
+                signature
+            ),
+            Error::<Test>::InvalidLinkSignature
+        );
+        assert_noop!(
+            Discord::link_account(
+                Origin::signed(bot()),
+                GUILD,
+                account,
+                500,
+                link_signature(&pair, 500)
+            ),
+            Error::<Test>::AlreadyLinked
+        );
+    });
+}
+
+#[test]
+fn unlink_account_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let pair = sr25519::Pair::from_seed(&[7; 32]);
+        let account = AccountId::from(pair.public());
+
+        assert_noop!(
+            Discord::unlink_account(Origin::signed(account.clone())),
+            Error::<Test>::AccountNotLinked
+        );
+
+        assert_ok!(Discord::link_account(
+            Origin::signed(bot()),
+            GUILD,
+            account.clone(),
+            500,
+            link_signature(&pair, 500)
+        ));
+        assert_ok!(Discord::unlink_account(Origin::signed(account.clone())));
+
+        assert_eq!(Discord::discord_user_of(&account), None);
+        assert_eq!(Discord::account_of_discord_user(500), None);
+        System::assert_last_event(DiscordEvent::AccountUnlinked(account, 500).into());
+    });
+}
+
+/// Takes up every slot of the scheduler in `blocks` with a task that does nothing.
+fn fill_scheduler(blocks: std::ops::Range<u64>) {
+    let task = ScheduledTask::BanExpiry(GUILD, bob());
+
+    for block in blocks {
+        crate::Scheduled::<Test>::mutate(
+            block,
+            |tasks| {
+                while tasks.try_push(task.clone()).is_ok() {}
+            },
+        );
+    }
+}
+
+/// Gates a fresh role on `min_balance`, returning it.
+fn gated_role(min_balance: u64) -> RoleIndex {
+    let role = create_role(None, 1, PermissionSet::empty());
+
+    assert_ok!(Discord::set_token_gate(
+        Origin::signed(bot()),
+        GUILD,
+        RoleRef::Index(role),
+        Some(min_balance)
+    ));
+
+    role
+}
+
+#[test]
+fn set_token_gate_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+        let whale = gated_role(100);
+        System::assert_last_event(DiscordEvent::TokenGateSet(GUILD, whale, Some(100)).into());
+
+        assert_noop!(
+            Discord::set_token_gate(Origin::signed(alice()), GUILD, RoleRef::Index(whale), None),
+            Error::<Test>::NoPermission
+        );
+
+        // Setting the gate again replaces it.
+        assert_ok!(Discord::set_token_gate(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Index(whale),
+            Some(200)
+        ));
+        assert_eq!(Discord::token_gates(GUILD).into_inner(), vec![(whale, 200)]);
+
+        gated_role(50);
+        let role = create_role(None, 1, PermissionSet::empty());
+        assert_noop!(
+            Discord::set_token_gate(Origin::signed(bot()), GUILD, RoleRef::Index(role), Some(10)),
+            Error::<Test>::TooManyTokenGates
+        );
+
+        assert_ok!(Discord::set_token_gate(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Index(whale),
+            None
+        ));
+        assert_eq!(Discord::token_gates(GUILD).len(), 1);
+        System::assert_last_event(DiscordEvent::TokenGateSet(GUILD, whale, None).into());
+    });
+}
+
+#[test]
+fn token_gated_roles_follow_balance() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+        let whale = gated_role(100);
+        Balances::make_free_balance_be(&alice(), 150);
+
+        assert_ok!(Discord::refresh_token_gates(
+            Origin::signed(bob()),
+            GUILD,
+            alice()
+        ));
+
+        assert!(Discord::has_role(GUILD, &alice(), whale));
+        System::assert_last_event(
+            DiscordEvent::TokenGatedRoleGranted(GUILD, alice(), whale).into(),
+        );
+
+        // The balance dropping is picked up by the periodic check.
+        Balances::make_free_balance_be(&alice(), 50);
+        run_to_block(11);
+
+        assert!(Discord::get_member(GUILD, alice())
+            .unwrap()
+            .roles
+            .is_empty());
+        System::assert_last_event(
+            DiscordEvent::TokenGatedRoleRevoked(GUILD, alice(), whale).into(),
+        );
+
+        Balances::make_free_balance_be(&alice(), 100);
+        run_to_block(21);
+
+        assert_eq!(
+            Discord::get_member(GUILD, alice())
+                .unwrap()
+                .roles
+                .into_inner(),
+            vec![whale]
+        );
+    });
+}
+
+#[test]
+fn token_gate_checks_stop_when_member_leaves() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+
+        assert_noop!(
+            Discord::refresh_token_gates(Origin::signed(bob()), GUILD, alice()),
+            Error::<Test>::NotAMemberOfTheGuild
+        );
+
+        add_member(alice());
+        gated_role(100);
+        assert_ok!(Discord::refresh_token_gates(
+            Origin::signed(alice()),
+            GUILD,
+            alice()
+        ));
+        assert!(crate::TokenGateChecks::<Test>::contains_key(GUILD, alice()));
+
+        assert_ok!(Discord::remove_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice()
+        ));
+        run_to_block(11);
+
+        assert!(!crate::TokenGateChecks::<Test>::contains_key(
+            GUILD,
+            alice()
+        ));
+        assert!(Discord::scheduled(21).is_empty());
+    });
+}
+
+#[test]
+fn added_members_are_enrolled_in_token_gate_checks() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let whale = gated_role(100);
+        Balances::make_free_balance_be(&alice(), 150);
+
+        add_member(alice());
+
+        assert!(Discord::has_role(GUILD, &alice(), whale));
+        assert!(crate::TokenGateChecks::<Test>::contains_key(GUILD, alice()));
+
+        Balances::make_free_balance_be(&alice(), 50);
+        run_to_block(11);
+
+        assert!(!Discord::has_role(GUILD, &alice(), whale));
+    });
+}
+
+#[test]
+fn token_gate_checks_apply_gates_when_the_scheduler_is_full() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let whale = gated_role(100);
+        add_member(alice());
+        Balances::make_free_balance_be(&alice(), 150);
+        assert_ok!(Discord::refresh_token_gates(
+            Origin::signed(alice()),
+            GUILD,
+            alice()
+        ));
+
+        // Every block the next check could land on is taken.
+        fill_scheduler(21..31);
+
+        Balances::make_free_balance_be(&alice(), 50);
+        run_to_block(11);
+
+        assert!(!Discord::has_role(GUILD, &alice(), whale));
+        assert!(!crate::TokenGateChecks::<Test>::contains_key(
+            GUILD,
+            alice()
+        ));
+        System::assert_has_event(DiscordEvent::TokenGateCheckNotScheduled(GUILD, alice()).into());
+    });
+}
+
+#[test]
+fn members_are_added_when_the_scheduler_is_full() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let whale = gated_role(100);
+        Balances::make_free_balance_be(&alice(), 150);
+
+        // Every block the first check could land on is taken.
+        fill_scheduler(11..21);
+
+        add_member(alice());
+
+        assert!(Discord::has_role(GUILD, &alice(), whale));
+        assert!(!crate::TokenGateChecks::<Test>::contains_key(
+            GUILD,
+            alice()
+        ));
+        System::assert_has_event(DiscordEvent::TokenGateCheckNotScheduled(GUILD, alice()).into());
+    });
+}
+
+#[test]
+fn existing_members_are_checked_once_refreshed() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+        Balances::make_free_balance_be(&alice(), 150);
+        let whale = gated_role(100);
+
+        // Setting the guild's first gate leaves its members alone.
+        run_to_block(11);
+
+        assert!(!Discord::has_role(GUILD, &alice(), whale));
+        assert!(!crate::TokenGateChecks::<Test>::contains_key(
+            GUILD,
+            alice()
+        ));
+
+        assert_ok!(Discord::refresh_token_gates(
+            Origin::signed(bot()),
+            GUILD,
+            alice()
+        ));
+
+        assert!(Discord::has_role(GUILD, &alice(), whale));
+        assert!(crate::TokenGateChecks::<Test>::contains_key(GUILD, alice()));
+    });
+}
+
+#[test]
+fn token_gates_end_subscriptions_to_revoked_roles() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = subscription_role();
+        add_member(alice());
+        assert_ok!(Discord::subscribe(
+            Origin::signed(alice()),
+            GUILD,
+            RoleRef::Index(role)
+        ));
+
+        assert_ok!(Discord::set_token_gate(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Index(role),
+            Some(INITIAL_BALANCE)
+        ));
+        assert_ok!(Discord::refresh_token_gates(
+            Origin::signed(bot()),
+            GUILD,
+            alice()
+        ));
+
+        assert!(!Discord::has_role(GUILD, &alice(), role));
+        assert_eq!(Discord::subscription(GUILD, (alice(), role)), None);
+        System::assert_has_event(DiscordEvent::SubscriptionExpired(GUILD, alice(), role).into());
+    });
+}
+
+#[test]
+fn delete_role_removes_token_gate() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let whale = gated_role(100);
+
+        assert_ok!(Discord::delete_role(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Index(whale),
+            0
+        ));
+
+        assert!(Discord::token_gates(GUILD).is_empty());
+    });
+}
+
+fn plan(price: u64, period: u64, grace_period: u64) -> SubscriptionPlan<u64, u64> {
+    SubscriptionPlan {
+        price,
+        period,
+        grace_period,
+    }
+}
+
+/// A fresh role members can subscribe to for 10 every 10 blocks, with 5 blocks of grace.
+fn subscription_role() -> RoleIndex {
+    let role = create_role(None, 1, PermissionSet::empty());
+
+    assert_ok!(Discord::set_subscription_plan(
+        Origin::signed(bot()),
+        GUILD,
+        RoleRef::Index(role),
+        Some(plan(10, 10, 5))
+    ));
+
+    role
+}
+
+#[test]
+fn set_subscription_plan_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+        let role = subscription_role();
+        System::assert_last_event(
+            DiscordEvent::SubscriptionPlanSet(GUILD, role, Some(plan(10, 10, 5))).into(),
+        );
+        assert_eq!(
+            Discord::subscription_plan(GUILD, role),
+            Some(plan(10, 10, 5))
+        );
+
+        assert_noop!(
+            Discord::set_subscription_plan(
+                Origin::signed(alice()),
+                GUILD,
+                RoleRef::Index(role),
+                None
+            ),
+            Error::<Test>::NoPermission
+        );
+
+        for invalid in [plan(10, 0, 0), plan(10, 10, 10)] {
+            assert_noop!(
+                Discord::set_subscription_plan(
+                    Origin::signed(bot()),
+                    GUILD,
+                    RoleRef::Index(role),
+                    Some(invalid)
+                ),
+                Error::<Test>::InvalidSubscriptionPlan
+            );
+        }
+
+        assert_ok!(Discord::set_subscription_plan(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Index(role),
+            None
+        ));
+        assert_eq!(Discord::subscription_plan(GUILD, role), None);
+
+        // Deleting the role removes its plan.
+        let role = subscription_role();
+        assert_ok!(Discord::delete_role(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Index(role),
+            1
+        ));
+        assert_eq!(Discord::subscription_plan(GUILD, role), None);
+    });
+}
+
+#[test]
+fn subscribe_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = subscription_role();
+        let unsold = create_role(None, 1, PermissionSet::empty());
+        Balances::make_free_balance_be(&alice(), 25);
+
+        assert_noop!(
+            Discord::subscribe(Origin::signed(alice()), GUILD, RoleRef::Index(role)),
+            Error::<Test>::NotAMemberOfTheGuild
+        );
+
+        add_member(alice());
+        add_member(bob());
+        Balances::make_free_balance_be(&bob(), 5);
+
+        assert_noop!(
+            Discord::subscribe(Origin::signed(alice()), GUILD, RoleRef::Index(unsold)),
+            Error::<Test>::NoSubscriptionPlan
+        );
+        assert_noop!(
+            Discord::subscribe(Origin::signed(bob()), GUILD, RoleRef::Index(role)),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+
+        assert_ok!(Discord::subscribe(
+            Origin::signed(alice()),
+            GUILD,
+            RoleRef::Index(role)
+        ));
+
+        System::assert_last_event(DiscordEvent::Subscribed(GUILD, alice(), role, 11).into());
+        assert_eq!(Balances::free_balance(alice()), 15);
+        assert_eq!(Balances::free_balance(Discord::guild_account(GUILD)), 10);
+        assert!(Discord::has_role(GUILD, &alice(), role));
+        assert_eq!(
+            Discord::subscription(GUILD, (alice(), role)),
+            Some(Subscription {
+                paid_until: 11,
+                auto_renew: true
+            })
+        );
+
+        assert_noop!(
+            Discord::subscribe(Origin::signed(alice()), GUILD, RoleRef::Index(role)),
+            Error::<Test>::AlreadySubscribed
+        );
+    });
+}
+
+#[test]
+fn subscriptions_renew_until_payment_fails() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+        let role = subscription_role();
+        Balances::make_free_balance_be(&alice(), 25);
+        assert_ok!(Discord::subscribe(
+            Origin::signed(alice()),
+            GUILD,
+            RoleRef::Index(role)
+        ));
+
+        run_to_block(11);
+
+        System::assert_last_event(
+            DiscordEvent::SubscriptionRenewed(GUILD, alice(), role, 21).into(),
+        );
+        assert_eq!(Balances::free_balance(alice()), 5);
+        assert_eq!(Balances::free_balance(Discord::guild_account(GUILD)), 20);
+
+        // The member keeps the role through the grace period.
+        run_to_block(21);
+
+        System::assert_last_event(
+            DiscordEvent::SubscriptionPaymentFailed(GUILD, alice(), role, 26).into(),
+        );
+        assert!(Discord::has_role(GUILD, &alice(), role));
+
+        run_to_block(26);
+
+        System::assert_last_event(DiscordEvent::SubscriptionExpired(GUILD, alice(), role).into());
+        assert!(!Discord::has_role(GUILD, &alice(), role));
+        assert_eq!(Discord::subscription(GUILD, (alice(), role)), None);
+    });
+}
+
+#[test]
+fn subscriptions_renew_when_the_scheduler_is_full() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+        let role = subscription_role();
+        Balances::make_free_balance_be(&alice(), 25);
+        assert_ok!(Discord::subscribe(
+            Origin::signed(alice()),
+            GUILD,
+            RoleRef::Index(role)
+        ));
+
+        // Every block other tasks could be pushed back to is taken.
+        fill_scheduler(21..31);
+
+        run_to_block(11);
+
+        System::assert_last_event(
+            DiscordEvent::SubscriptionRenewed(GUILD, alice(), role, 21).into(),
+        );
+        assert_eq!(Balances::free_balance(alice()), 5);
+        assert_eq!(
+            Discord::scheduled(31).into_inner(),
+            vec![ScheduledTask::SubscriptionRenewal(GUILD, alice(), role)]
+        );
+    });
+}
+
+#[test]
+fn payment_within_grace_period_renews_subscription() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+        let role = subscription_role();
+        Balances::make_free_balance_be(&alice(), 15);
+        assert_ok!(Discord::subscribe(
+            Origin::signed(alice()),
+            GUILD,
+            RoleRef::Index(role)
+        ));
+
+        run_to_block(11);
+        System::assert_last_event(
+            DiscordEvent::SubscriptionPaymentFailed(GUILD, alice(), role, 16).into(),
+        );
+
+        // Late payments don't shift the billing period.
+        Balances::make_free_balance_be(&alice(), 15);
+        run_to_block(16);
+
+        System::assert_last_event(
+            DiscordEvent::SubscriptionRenewed(GUILD, alice(), role, 21).into(),
+        );
+        assert!(Discord::has_role(GUILD, &alice(), role));
+    });
+}
+
+#[test]
+fn cancelled_subscriptions_expire_at_period_end() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+        let role = subscription_role();
+        Balances::make_free_balance_be(&alice(), 25);
+        assert_ok!(Discord::subscribe(
+            Origin::signed(alice()),
+            GUILD,
+            RoleRef::Index(role)
+        ));
+
+        assert_noop!(
+            Discord::cancel_subscription(Origin::signed(bob()), GUILD, RoleRef::Index(role)),
+            Error::<Test>::NotSubscribed
+        );
+
+        assert_ok!(Discord::cancel_subscription(
+            Origin::signed(alice()),
+            GUILD,
+            RoleRef::Index(role)
+        ));
+        System::assert_last_event(DiscordEvent::SubscriptionCancelled(GUILD, alice(), role).into());
+
+        run_to_block(10);
+        assert!(Discord::has_role(GUILD, &alice(), role));
+
+        run_to_block(11);
+
+        System::assert_last_event(DiscordEvent::SubscriptionExpired(GUILD, alice(), role).into());
+        assert!(!Discord::has_role(GUILD, &alice(), role));
+        assert_eq!(Balances::free_balance(alice()), 15);
+    });
+}
+
+#[test]
+fn subscriptions_end_when_their_role_is_lost() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = subscription_role();
+        for member in [alice(), bob(), charlie()] {
+            add_member(member.clone());
+            assert_ok!(Discord::subscribe(
+                Origin::signed(member),
+                GUILD,
+                RoleRef::Index(role)
+            ));
+        }
+
+        assert_ok!(Discord::revoke_role(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            RoleRef::Index(role)
+        ));
+        System::assert_has_event(DiscordEvent::SubscriptionExpired(GUILD, alice(), role).into());
+        assert_eq!(Discord::subscription(GUILD, (alice(), role)), None);
+
+        assert_ok!(Discord::kick_member(Origin::signed(bot()), GUILD, bob()));
+        assert_eq!(Discord::subscription(GUILD, (bob(), role)), None);
+
+        // Renewals also check the role is still held.
+        crate::GuildMembers::<Test>::mutate(GUILD, charlie(), |guild_member| {
+            if let Some(guild_member) = guild_member {
+                guild_member.roles.retain(|held| *held != role);
+            }
+        });
+
+        // Getting the role back for free doesn't charge the former subscription.
+        assert_ok!(Discord::assign_role(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            RoleRef::Index(role)
+        ));
+        run_to_block(12);
+
+        assert!(Discord::has_role(GUILD, &alice(), role));
+        assert_eq!(Balances::free_balance(alice()), INITIAL_BALANCE - 10);
+        assert_eq!(Discord::subscription(GUILD, (charlie(), role)), None);
+        assert_eq!(Balances::free_balance(charlie()), INITIAL_BALANCE - 10);
+        System::assert_last_event(DiscordEvent::SubscriptionExpired(GUILD, charlie(), role).into());
+    });
+}
+
+#[test]
+fn member_and_role_deposits_are_reserved_and_released() {
+    new_test_ext().execute_with(|| {
+        // The @everyone role holds a deposit.
+        setup_guild();
+        assert_eq!(Balances::reserved_balance(bot()), 5);
+
+        add_member(alice());
+        assert_eq!(Balances::reserved_balance(bot()), 8);
+        assert_eq!(
+            Discord::get_deposit(GUILD, DepositKey::Member(alice())),
+            Some((bot(), 3))
+        );
+
+        let role = create_role(None, 1, PermissionSet::empty());
+        assert_eq!(Balances::reserved_balance(bot()), 13);
+
+        assert_ok!(Discord::delete_role(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Index(role),
+            1
+        ));
+        assert_eq!(Balances::reserved_balance(bot()), 8);
+        assert_eq!(Discord::get_deposit(GUILD, DepositKey::Role(role)), None);
+
+        assert_ok!(Discord::remove_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice()
+        ));
+        assert_eq!(Balances::reserved_balance(bot()), 5);
+        assert_eq!(
+            Discord::get_deposit(GUILD, DepositKey::Member(alice())),
+            None
+        );
+
+        Balances::make_free_balance_be(&bot(), 2);
+        assert_noop!(
+            Discord::add_member(Origin::signed(bot()), GUILD, alice(), false, false),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+    });
+}
+
+#[test]
+fn ban_deposits_pay_for_reasons() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member(alice());
+        add_member(bob());
+
+        assert_ok!(Discord::ban_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            b"spam".to_vec(),
+            None
+        ));
+        assert_eq!(Balances::reserved_balance(bot()), 15);
+        assert_eq!(
+            Discord::get_deposit(GUILD, DepositKey::Ban(alice())),
+            Some((bot(), 4))
+        );
+
+        // Restored members keep their record, and its deposit.
+        assert_ok!(Discord::unban_member(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            true
+        ));
+        assert_eq!(Balances::reserved_balance(bot()), 11);
+        assert!(Discord::get_deposit(GUILD, DepositKey::Member(alice())).is_some());
+
+        assert_ok!(Discord::ban_member(
+            Origin::signed(bot()),
+            GUILD,
+            bob(),
+            b"x".to_vec(),
+            Some(3)
+        ));
+        assert_eq!(Balances::reserved_balance(bot()), 12);
+
+        run_to_block(3);
+
+        assert_eq!(Balances::reserved_balance(bot()), 8);
+        assert_eq!(Discord::get_deposit(GUILD, DepositKey::Member(bob())), None);
+    });
+}
+
+#[test]
+fn guild_account_pays_deposits_of_members() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member_with_role(alice(), 2, Permissions::MANAGE_ROLES.into());
+        let alice_creates_role = || {
+            Discord::create_role(
+                Origin::signed(alice()),
+                GUILD,
+                None,
+                b"role".to_vec(),
+                0,
+                false,
+                1,
+                PermissionSet::empty(),
+                false,
+            )
+        };
+
+        assert_noop!(
+            alice_creates_role(),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+
+        Balances::make_free_balance_be(&Discord::guild_account(GUILD), 100);
+        assert_ok!(alice_creates_role());
+
+        assert_eq!(Balances::reserved_balance(Discord::guild_account(GUILD)), 5);
+        assert_eq!(Balances::reserved_balance(alice()), 0);
+    });
+}
+
+/// A role held by alice and bob letting them approve spends, two of which are needed.
+fn treasury_role() -> RoleIndex {
+    let role = add_member_with_role(alice(), 1, PermissionSet::empty());
+    add_member(bob());
+
+    assert_ok!(Discord::assign_role(
+        Origin::signed(bot()),
+        GUILD,
+        bob(),
+        RoleRef::Index(role)
+    ));
+    assert_ok!(Discord::set_treasury_approvers(
+        Origin::signed(bot()),
+        GUILD,
+        Some(RoleRef::Index(role)),
+        2
+    ));
+
+    role
+}
+
+#[test]
+fn set_treasury_approvers_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member_with_role(alice(), 2, Permissions::MANAGE_GUILD.into());
+        let role = create_role(None, 1, PermissionSet::empty());
+
+        assert_noop!(
+            Discord::set_treasury_approvers(Origin::signed(bob()), GUILD, None, 2),
+            Error::<Test>::NoPermission
+        );
+
+        assert_ok!(Discord::set_treasury_approvers(
+            Origin::signed(alice()),
+            GUILD,
+            Some(RoleRef::Index(role)),
+            2
+        ));
+
+        System::assert_last_event(DiscordEvent::TreasuryApproversSet(GUILD, Some(role), 2).into());
+        assert_eq!(Discord::treasury_role(GUILD), Some(role));
+        assert_eq!(Discord::spend_threshold(GUILD), 2);
+
+        assert_noop!(
+            Discord::set_treasury_approvers(Origin::signed(alice()), GUILD, None, 1),
+            Error::<Test>::ThresholdTooLow
+        );
+        assert_noop!(
+            Discord::set_treasury_approvers(Origin::signed(alice()), GUILD, None, 4),
+            Error::<Test>::ThresholdTooHigh
+        );
+
+        // Deleting the role leaves spends to members holding `MANAGE_GUILD`.
+        assert_ok!(Discord::delete_role(
+            Origin::signed(bot()),
+            GUILD,
+            RoleRef::Index(role),
+            1
+        ));
+        assert_eq!(Discord::treasury_role(GUILD), None);
+    });
+}
+
+#[test]
+fn spends_are_paid_once_approved() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        treasury_role();
+        add_member(charlie());
+        let treasury = Discord::guild_account(GUILD);
+
+        assert_noop!(
+            Discord::propose_spend(
+                Origin::signed(charlie()),
+                GUILD,
+                account(5),
+                30,
+                b"prize".to_vec()
+            ),
+            Error::<Test>::NoPermission
+        );
+
+        assert_ok!(Discord::propose_spend(
+            Origin::signed(alice()),
+            GUILD,
+            account(5),
+            30,
+            b"prize".to_vec()
+        ));
+
+        System::assert_last_event(DiscordEvent::SpendApproved(GUILD, 0, alice(), 1).into());
+        assert_eq!(
+            Discord::spend_proposal(GUILD, 0)
+                .unwrap()
+                .approvals
+                .into_inner(),
+            vec![alice()]
+        );
+
+        assert_noop!(
+            Discord::approve_spend(Origin::signed(alice()), GUILD, 0),
+            Error::<Test>::AlreadyApproved
+        );
+        assert_noop!(
+            Discord::approve_spend(Origin::signed(bob()), GUILD, 0),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+
+        Balances::make_free_balance_be(&treasury, 100);
+        assert_ok!(Discord::approve_spend(Origin::signed(bob()), GUILD, 0));
+
+        System::assert_last_event(DiscordEvent::SpendPaid(GUILD, 0, account(5), 30).into());
+        assert_eq!(Balances::free_balance(account(5)), 30);
+        assert_eq!(Balances::free_balance(&treasury), 70);
+        assert_eq!(Discord::spend_proposal(GUILD, 0), None);
+        assert_eq!(
+            Discord::spend_history(GUILD).into_inner(),
+            vec![Spend {
+                index: 0,
+                beneficiary: account(5),
+                amount: 30,
+                paid_at: 1
+            }]
+        );
+
+        assert_noop!(
+            Discord::approve_spend(Origin::signed(bob()), GUILD, 0),
+            Error::<Test>::UnknownSpend
+        );
+    });
+}
+
+#[test]
+fn spend_approvals_drop_former_approvers() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        let role = treasury_role();
+        Balances::make_free_balance_be(&Discord::guild_account(GUILD), 100);
+
+        assert_ok!(Discord::propose_spend(
+            Origin::signed(alice()),
+            GUILD,
+            account(5),
+            30,
+            Vec::new()
+        ));
+        assert_ok!(Discord::revoke_role(
+            Origin::signed(bot()),
+            GUILD,
+            alice(),
+            RoleRef::Index(role)
+        ));
+        assert_ok!(Discord::approve_spend(Origin::signed(bob()), GUILD, 0));
+
+        System::assert_last_event(DiscordEvent::SpendApproved(GUILD, 0, bob(), 1).into());
+        assert_eq!(
+            Discord::spend_proposal(GUILD, 0)
+                .unwrap()
+                .approvals
+                .into_inner(),
+            vec![bob()]
+        );
+        assert_eq!(Balances::free_balance(account(5)), 0);
+    });
+}
+
+#[test]
+fn spend_history_is_bounded() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        add_member_with_role(alice(), 2, Permissions::MANAGE_GUILD.into());
+        add_member_with_role(charlie(), 2, Permissions::MANAGE_GUILD.into());
+        Balances::make_free_balance_be(&Discord::guild_account(GUILD), 100);
+
+        for (index, amount) in [(0, 10), (1, 20), (2, 30)] {
+            assert_ok!(Discord::propose_spend(
+                Origin::signed(alice()),
+                GUILD,
+                account(5),
+                amount,
+                Vec::new()
+            ));
+
+            // Without a configured threshold, spends still need a second approval.
+            assert!(Discord::spend_proposal(GUILD, index).is_some());
+
+            assert_ok!(Discord::approve_spend(
+                Origin::signed(charlie()),
+                GUILD,
+                index
+            ));
+        }
+
+        assert_eq!(
+            Discord::spend_history(GUILD)
+                .iter()
+                .map(|spend| (spend.index, spend.amount))
+                .collect::<Vec<_>>(),
+            vec![(1, 20), (2, 30)]
+        );
+    });
+}
+
+#[test]
+fn cancel_spend_works() {
+    new_test_ext().execute_with(|| {
+        setup_guild();
+        treasury_role();
+        add_member_with_role(charlie(), 2, Permissions::MANAGE_GUILD.into());
+
+        for proposer in [alice(), bob()] {
+            assert_ok!(Discord::propose_spend(
+                Origin::signed(proposer),
+                GUILD,
+                account(5),
+                30,
+                Vec::new()
+            ));
+        }
+
+        assert_noop!(
+            Discord::cancel_spend(Origin::signed(bob()), GUILD, 0),
+            Error::<Test>::NoPermission
+        );
+
+        assert_ok!(Discord::cancel_spend(Origin::signed(alice()), GUILD, 0));
+        System::assert_last_event(DiscordEvent::SpendCancelled(GUILD, 0).into());
+        assert_ok!(Discord::cancel_spend(Origin::signed(charlie()), GUILD, 1));
+
+        assert_noop!(
+            Discord::cancel_spend(Origin::signed(alice()), GUILD, 0),
+            Error::<Test>::UnknownSpend
+        );
+    });
+}
+
+#[test]
+fn genesis_config_works() {
+    let mut storage = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    crate::GenesisConfig::<Test> {
+        guilds: vec![(GUILD, b"Society".to_vec())],
+        bots: vec![(GUILD, bot(), b"bot".to_vec(), 100, 1 << 1)],
+        roles: vec![
+            (GUILD, GUILD, b"@everyone".to_vec(), 0, 1 << 10),
+            (GUILD, 100, b"mods".to_vec(), 1, 1 << 1),
+        ],
+        members: vec![(GUILD, alice(), vec![100]), (GUILD, bob(), vec![])],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    sp_io::TestExternalities::new(storage).execute_with(|| {
+        assert_eq!(
+            Discord::get_guild(GUILD).unwrap().name.into_inner(),
+            b"Society".to_vec()
+        );
+        assert_eq!(
+            Discord::get_bot(GUILD, bot()).unwrap().capabilities,
+            BotCapabilities::MANAGE_MEMBERS
+        );
+        assert_eq!(Discord::everyone_role(GUILD), Some(0));
+        assert_eq!(Discord::get_role_index(GUILD, 100), Some(1));
+        assert_eq!(Discord::next_role_index(GUILD), 2);
+        assert_eq!(
+            Discord::get_role(GUILD, 1).unwrap().name.into_inner(),
+            b"mods".to_vec()
+        );
+        assert_eq!(
+            Discord::effective_permissions(GUILD, &alice(), None),
+            PermissionSet::VIEW_CHANNEL | PermissionSet::KICK_MEMBERS
+        );
+        assert_eq!(
+            Discord::effective_permissions(GUILD, &bob(), None),
+            PermissionSet::VIEW_CHANNEL
+        );
+    });
+}
+
+mod origins {
+    use super::*;
+    use crate::{EnsurePermission, EnsureRole};
+    use frame_support::{parameter_types, traits::EnsureOrigin};
+
+    parameter_types! {
+        pub const Society: u64 = GUILD;
+        pub const Moderators: u64 = 100;
+        pub const Kick: PermissionSet = PermissionSet::KICK_MEMBERS;
+    }
+
+    type EnsureModerator = EnsureRole<Test, Society, Moderators>;
+    type EnsureKick = EnsurePermission<Test, Society, Kick>;
+
+    #[test]
+    fn ensure_role_works() {
+        new_test_ext().execute_with(|| {
+            setup_guild();
+            let mods = create_role(Some(100), 1, PermissionSet::empty());
+            add_member(alice());
+            assert_ok!(Discord::assign_role(
+                Origin::signed(bot()),
+                GUILD,
+                alice(),
+                RoleRef::Index(mods)
+            ));
+            let impostor = add_member_with_role(bob(), 1, PermissionSet::all());
+
+            // Only the Discord id counts, not the name of the role.
+            let name = Discord::get_role(GUILD, mods).unwrap().name.into_inner();
+            assert_ok!(Discord::update_role(
+                Origin::signed(bot()),
+                GUILD,
+                RoleRef::Index(impostor),
+                name,
+                0,
+                false,
+                1,
+                PermissionSet::all(),
+                false
+            ));
+
+            assert_eq!(
+                EnsureModerator::try_origin(Origin::signed(alice())).ok(),
+                Some(alice())
+            );
+            assert!(EnsureModerator::try_origin(Origin::signed(bob())).is_err());
+            assert!(EnsureModerator::try_origin(Origin::signed(charlie())).is_err());
+            assert!(EnsureModerator::try_origin(Origin::root()).is_err());
+        });
+    }
+
+    #[test]
+    fn ensure_permission_works() {
+        new_test_ext().execute_with(|| {
+            setup_guild();
+            add_member_with_role(
+                alice(),
+                1,
+                PermissionSet::KICK_MEMBERS | PermissionSet::BAN_MEMBERS,
+            );
+            add_member_with_role(bob(), 1, PermissionSet::BAN_MEMBERS);
+            add_member_with_role(charlie(), 1, PermissionSet::ADMINISTRATOR);
+
+            assert_eq!(
+                EnsureKick::try_origin(Origin::signed(alice())).ok(),
+                Some(alice())
+            );
+            assert!(EnsureKick::try_origin(Origin::signed(bob())).is_err());
+            assert_eq!(
+                EnsureKick::try_origin(Origin::signed(charlie())).ok(),
+                Some(charlie())
+            );
+            assert!(EnsureKick::try_origin(Origin::signed(bot())).is_err());
+            assert!(EnsureKick::try_origin(Origin::root()).is_err());
+        });
+    }
+}
+
+mod migrations {
+    use super::*;
+    use crate::migrations::{v0, MigrateToV1, MigrateToV2};
+    use codec::Encode;
+    use frame_support::{
+        parameter_types,
+        storage::migration,
+        traits::{OnRuntimeUpgrade, StorageVersion},
+        Blake2_128Concat, StorageHasher,
+    };
+
+    parameter_types! {
+        pub const LegacyGuild: u64 = GUILD;
+    }
+
+    fn put_v0<K: Encode, V: Encode>(item: &[u8], key: K, value: V) {
+        migration::put_storage_value(
+            b"Discord",
+            item,
+            &Blake2_128Concat::hash(&key.encode()),
+            value,
+        );
+    }
+
+    fn v0_member(account: AccountId, roles: &[&[u8]]) -> v0::GuildMember<AccountId> {
+        v0::GuildMember {
+            account,
+            roles: roles.iter().map(|role| role.to_vec()).collect(),
+            deaf: false,
+            mute: true,
+        }
+    }
+
+    fn v0_role(id: Option<u64>, name: &[u8], permissions: Vec<Permissions>) -> v0::Role<u64> {
+        v0::Role {
+            id,
+            name: name.to_vec(),
+            color: 7,
+            hoist: true,
+            position: 1,
+            permissions,
+            managed: false,
+            mentionable: true,
+        }
+    }
+
+    /// Populates the storage of the unversioned pallet.
+    fn populate_v0() {
+        put_v0(
+            b"Roles",
+            b"admin".to_vec(),
+            v0_role(Some(10), b"admin", vec![Permissions::ADMINISTRATOR]),
+        );
+        put_v0(
+            b"Roles",
+            b"member".to_vec(),
+            v0_role(None, b"member", vec![Permissions::VIEW_CHANNEL]),
+        );
+        put_v0(
+            b"Roles",
+            vec![b'x'; 17],
+            v0_role(Some(11), &[b'x'; 17], vec![Permissions::KICK_MEMBERS]),
+        );
+        put_v0(
+            b"GuildMembers",
+            alice(),
+            v0_member(alice(), &[b"admin", b"member", &[b'x'; 17]]),
+        );
+        put_v0(b"GuildMembers", bob(), v0_member(bob(), &[]));
+        put_v0(
+            b"BannedMembers",
+            charlie(),
+            (v0_member(charlie(), &[b"member"]), vec![b'r'; 40]),
+        );
+        put_v0(b"Bots", bot(), ());
+    }
+
+    #[test]
+    fn migrates_v0_state() {
+        new_test_ext().execute_with(|| {
+            populate_v0();
+            assert_eq!(StorageVersion::get::<Discord>(), 0);
+
+            MigrateToV1::<Test, LegacyGuild>::on_runtime_upgrade();
+
+            assert_eq!(StorageVersion::get::<Discord>(), 1);
+            assert!(Discord::get_guild(GUILD).is_some());
+            assert!(crate::Bots::<Test>::contains_key(GUILD, bot()));
+
+            // The role with an overlong name is dropped.
+            assert_eq!(Discord::next_role_index(GUILD), 2);
+            assert_eq!(Discord::get_role_index(GUILD, 11), None);
+            let admin = Discord::get_role_index(GUILD, 10).unwrap();
+            let member = 1 - admin;
+            let admin_role = Discord::get_role(GUILD, admin).unwrap();
+            assert_eq!(admin_role.name.into_inner(), b"admin".to_vec());
+            assert_eq!(admin_role.permissions, PermissionSet::ADMINISTRATOR);
+            assert_eq!(admin_role.color, 7);
+            assert!(admin_role.mentionable);
+            let member_role = Discord::get_role(GUILD, member).unwrap();
+            assert_eq!(member_role.name.into_inner(), b"member".to_vec());
+            assert_eq!(member_role.id, None);
+            assert_eq!(member_role.permissions, PermissionSet::VIEW_CHANNEL);
+
+            let alice_member = Discord::get_member(GUILD, alice()).unwrap();
+            assert_eq!(alice_member.roles.into_inner(), vec![admin, member]);
+            assert!(alice_member.mute);
+            assert_eq!(
+                Discord::effective_permissions(GUILD, &alice(), None),
+                PermissionSet::all()
+            );
+            assert!(Discord::get_member(GUILD, bob()).unwrap().roles.is_empty());
+
+            let (banned, reason, expires_at) =
+                Discord::get_banned_member(GUILD, charlie()).unwrap();
+            assert_eq!(banned.roles.into_inner(), vec![member]);
+            assert_eq!(reason.into_inner(), vec![b'r'; 32]);
+            assert_eq!(expires_at, None);
+
+            // Migrated state is usable by the calls.
+            MigrateToV2::<Test>::on_runtime_upgrade();
+            assert_ok!(Discord::unban_member(
+                Origin::signed(bot()),
+                GUILD,
+                charlie(),
+                true
+            ));
+            assert_ok!(Discord::kick_member(Origin::signed(alice()), GUILD, bob()));
+        });
+    }
+
+    #[test]
+    fn migration_only_runs_on_v0() {
+        new_test_ext().execute_with(|| {
+            populate_v0();
+            MigrateToV1::<Test, LegacyGuild>::on_runtime_upgrade();
+
+            let members = crate::GuildMembers::<Test>::iter().count();
+            MigrateToV1::<Test, LegacyGuild>::on_runtime_upgrade();
+
+            assert_eq!(crate::GuildMembers::<Test>::iter().count(), members);
+            assert_eq!(Discord::next_role_index(GUILD), 2);
+        });
+    }
+
+    #[test]
+    fn migrates_v1_bots() {
+        new_test_ext().execute_with(|| {
+            populate_v0();
+            MigrateToV1::<Test, LegacyGuild>::on_runtime_upgrade();
+            assert_eq!(Discord::get_bot(GUILD, bot()), None);
+
+            MigrateToV2::<Test>::on_runtime_upgrade();
+
+            assert_eq!(StorageVersion::get::<Discord>(), 2);
+            assert_eq!(
+                Discord::get_bot(GUILD, bot()),
+                Some(BotInfo {
+                    name: Default::default(),
+                    application_id: 0,
+                    capabilities: BotCapabilities::all(),
+                })
+            );
+        });
+    }
+
+    #[test]
+    fn v2_migration_only_runs_on_v1() {
+        new_test_ext().execute_with(|| {
+            populate_v0();
+            MigrateToV2::<Test>::on_runtime_upgrade();
+
+            assert_eq!(StorageVersion::get::<Discord>(), 0);
+        });
+    }
+
+    #[cfg(feature = "try-runtime")]
+    #[test]
+    fn migration_passes_try_runtime_checks() {
+        new_test_ext().execute_with(|| {
+            populate_v0();
+
+            assert_ok!(MigrateToV1::<Test, LegacyGuild>::pre_upgrade());
+            assert_ok!(MigrateToV2::<Test>::pre_upgrade());
+            MigrateToV1::<Test, LegacyGuild>::on_runtime_upgrade();
+            MigrateToV2::<Test>::on_runtime_upgrade();
+            assert_ok!(MigrateToV1::<Test, LegacyGuild>::post_upgrade());
+            assert_ok!(MigrateToV2::<Test>::post_upgrade());
+        });
+    }
+}
@@ -0,0 +1,500 @@
+//! Weights for pallet_discord
+//!
+//! NOT GENERATED: the figures below are hand-written placeholders that haven't been measured
+//! yet. Replace this file with the output of a benchmark run on reference hardware before
+//! relying on it, and regenerate it whenever a call or benchmark changes instead of editing the
+//! figures by hand:
+//!
+//! ./blockchain/scripts/benchmark.sh
+//!
+//! Calls that check a member's permissions are charged per role a member can hold, scaled by
+//! the runtime's `MaxRolesPerMember`. Likewise bot approvals scale with `MaxBotThreshold` and
+//! the `MaxOpenBotProposals` they may prune, and spend approvals with `MaxSpendApprovals`, as
+//! the permissions of earlier approvers are checked again. The `()` implementation has no
+//! runtime to read these bounds from and assumes 250 roles, 16 bots, 32 open proposals and 8
+//! approvals. Scheduled tasks are charged for the reads of a subscription renewal pushed back
+//! across `RENEWAL_LOOKAHEAD` full blocks and the writes of a ban expiry, the heaviest of them.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_discord.
+pub trait WeightInfo {
+	fn register_guild() -> Weight;
+	fn update_guild() -> Weight;
+	fn register_bot() -> Weight;
+	fn unregister_bot() -> Weight;
+	fn set_bot_threshold() -> Weight;
+	fn approve_bot_action() -> Weight;
+	fn add_member() -> Weight;
+	fn ban_member() -> Weight;
+	fn unban_member() -> Weight;
+	fn kick_member() -> Weight;
+	fn set_mute() -> Weight;
+	fn set_deaf() -> Weight;
+	fn timeout_member() -> Weight;
+	fn remove_member() -> Weight;
+	fn create_role() -> Weight;
+	fn confirm_role_id() -> Weight;
+	fn assign_role() -> Weight;
+	fn update_role() -> Weight;
+	fn delete_role(w: u32) -> Weight;
+	fn revoke_role() -> Weight;
+	fn create_channel(o: u32) -> Weight;
+	fn update_channel(o: u32) -> Weight;
+	fn delete_channel() -> Weight;
+	fn link_account() -> Weight;
+	fn unlink_account() -> Weight;
+	fn set_token_gate() -> Weight;
+	fn refresh_token_gates() -> Weight;
+	fn set_subscription_plan() -> Weight;
+	fn subscribe() -> Weight;
+	fn cancel_subscription() -> Weight;
+	fn set_treasury_approvers() -> Weight;
+	fn propose_spend() -> Weight;
+	fn approve_spend() -> Weight;
+	fn cancel_spend() -> Weight;
+	fn on_initialize(t: u32) -> Weight;
+}
+
+/// Weights for pallet_discord using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: crate::Config> WeightInfo for SubstrateWeight<T> {
+	fn register_guild() -> Weight {
+		(32_410_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn update_guild() -> Weight {
+		(30_120_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn register_bot() -> Weight {
+		(24_310_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn unregister_bot() -> Weight {
+		let o = T::MaxOpenBotProposals::get() as Weight;
+		(22_140_000 as Weight)
+			.saturating_add((2_000_000 as Weight).saturating_mul(o))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(o)))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(o)))
+	}
+	fn set_bot_threshold() -> Weight {
+		(19_840_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn approve_bot_action() -> Weight {
+		let b = T::MaxBotThreshold::get() as Weight;
+		let o = T::MaxOpenBotProposals::get() as Weight;
+		(27_480_000 as Weight)
+			.saturating_add((1_000_000 as Weight).saturating_mul(b))
+			.saturating_add((3_000_000 as Weight).saturating_mul(o))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(b)))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(o)))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(o)))
+	}
+	fn add_member() -> Weight {
+		(40_870_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn ban_member() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(146_640_000 as Weight)
+			.saturating_add((3_900_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	fn unban_member() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(96_380_000 as Weight)
+			.saturating_add((1_300_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	fn kick_member() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(126_020_000 as Weight)
+			.saturating_add((3_900_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn set_mute() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(116_220_000 as Weight)
+			.saturating_add((3_900_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn set_deaf() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(115_480_000 as Weight)
+			.saturating_add((3_900_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn timeout_member() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(128_610_000 as Weight)
+			.saturating_add((3_900_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn remove_member() -> Weight {
+		(37_450_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn create_role() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(105_910_000 as Weight)
+			.saturating_add((2_600_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	fn confirm_role_id() -> Weight {
+		(29_910_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn assign_role() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(98_020_000 as Weight)
+			.saturating_add((2_600_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn update_role() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(95_670_000 as Weight)
+			.saturating_add((2_600_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn delete_role(w: u32) -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(115_230_000 as Weight)
+			.saturating_add((2_600_000 as Weight).saturating_mul(r))
+			.saturating_add((14_930_000 as Weight).saturating_mul(w as Weight))
+			.saturating_add(T::DbWeight::get().reads(11 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(w as Weight)))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(w as Weight)))
+	}
+	fn revoke_role() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(96_890_000 as Weight)
+			.saturating_add((2_600_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn create_channel(o: u32) -> Weight {
+		(27_300_000 as Weight)
+			.saturating_add((186_000 as Weight).saturating_mul(o as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn update_channel(o: u32) -> Weight {
+		(29_450_000 as Weight)
+			.saturating_add((191_000 as Weight).saturating_mul(o as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn delete_channel() -> Weight {
+		(23_660_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn link_account() -> Weight {
+		(98_730_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	fn unlink_account() -> Weight {
+		(31_040_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn set_token_gate() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(101_860_000 as Weight)
+			.saturating_add((2_600_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn refresh_token_gates() -> Weight {
+		(47_580_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn set_subscription_plan() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(100_930_000 as Weight)
+			.saturating_add((2_600_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn subscribe() -> Weight {
+		(88_460_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	fn cancel_subscription() -> Weight {
+		(23_170_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn set_treasury_approvers() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(99_620_000 as Weight)
+			.saturating_add((2_600_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn propose_spend() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(153_450_000 as Weight)
+			.saturating_add((2_600_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(15 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	fn approve_spend() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		let a = T::MaxSpendApprovals::get() as Weight;
+		(112_870_000 as Weight)
+			.saturating_add((2_600_000 as Weight).saturating_mul(r).saturating_mul(a))
+			.saturating_add(T::DbWeight::get().reads(86 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r).saturating_mul(a)))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	fn cancel_spend() -> Weight {
+		let r = T::MaxRolesPerMember::get() as Weight;
+		(98_210_000 as Weight)
+			.saturating_add((2_600_000 as Weight).saturating_mul(r))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn on_initialize(t: u32) -> Weight {
+		(4_120_000 as Weight)
+			.saturating_add((161_740_000 as Weight).saturating_mul(t as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((106 as Weight).saturating_mul(t as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((5 as Weight).saturating_mul(t as Weight)))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn register_guild() -> Weight {
+		(32_410_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn update_guild() -> Weight {
+		(30_120_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn register_bot() -> Weight {
+		(24_310_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn unregister_bot() -> Weight {
+		(86_140_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(34 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(34 as Weight))
+	}
+	fn set_bot_threshold() -> Weight {
+		(19_840_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn approve_bot_action() -> Weight {
+		(139_480_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(50 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(34 as Weight))
+	}
+	fn add_member() -> Weight {
+		(40_870_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn ban_member() -> Weight {
+		(1_121_640_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(758 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn unban_member() -> Weight {
+		(421_380_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(259 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn kick_member() -> Weight {
+		(1_101_020_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(758 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn set_mute() -> Weight {
+		(1_091_220_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(756 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_deaf() -> Weight {
+		(1_090_480_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(756 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn timeout_member() -> Weight {
+		(1_103_610_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(757 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn remove_member() -> Weight {
+		(37_450_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn create_role() -> Weight {
+		(755_910_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(509 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	fn confirm_role_id() -> Weight {
+		(29_910_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn assign_role() -> Weight {
+		(748_020_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(509 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn update_role() -> Weight {
+		(745_670_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(509 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn delete_role(w: u32) -> Weight {
+		(765_230_000 as Weight)
+			.saturating_add((14_930_000 as Weight).saturating_mul(w as Weight))
+			.saturating_add(RocksDbWeight::get().reads(511 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(w as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(w as Weight)))
+	}
+	fn revoke_role() -> Weight {
+		(746_890_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(508 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn create_channel(o: u32) -> Weight {
+		(27_300_000 as Weight)
+			.saturating_add((186_000 as Weight).saturating_mul(o as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn update_channel(o: u32) -> Weight {
+		(29_450_000 as Weight)
+			.saturating_add((191_000 as Weight).saturating_mul(o as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn delete_channel() -> Weight {
+		(23_660_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn link_account() -> Weight {
+		(98_730_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn unlink_account() -> Weight {
+		(31_040_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn set_token_gate() -> Weight {
+		(751_860_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(510 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn refresh_token_gates() -> Weight {
+		(47_580_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn set_subscription_plan() -> Weight {
+		(750_930_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(510 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn subscribe() -> Weight {
+		(88_460_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn cancel_subscription() -> Weight {
+		(23_170_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_treasury_approvers() -> Weight {
+		(749_620_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(510 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn propose_spend() -> Weight {
+		(803_450_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(515 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn approve_spend() -> Weight {
+		(5_312_870_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4086 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	fn cancel_spend() -> Weight {
+		(748_210_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(509 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn on_initialize(t: u32) -> Weight {
+		(4_120_000 as Weight)
+			.saturating_add((161_740_000 as Weight).saturating_mul(t as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((106 as Weight).saturating_mul(t as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((5 as Weight).saturating_mul(t as Weight)))
+	}
+}